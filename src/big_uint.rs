@@ -0,0 +1,346 @@
+use std::cmp::Ordering;
+
+/// Unsigned integer of any size, with just the operations needed to print and round fractions
+/// exactly. Limbs are stored least significant first.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BigUint {
+    limbs: Vec<u64>,
+}
+
+impl BigUint {
+    pub fn zero() -> Self {
+        BigUint { limbs: Vec::new() }
+    }
+
+    /// `value * 2^shift`
+    pub fn from_shifted(value: u64, shift: u32) -> Self {
+        let mut limbs = vec![0; (shift / 64) as usize];
+        let shift = shift % 64;
+        limbs.push(value << shift);
+        if shift > 0 {
+            limbs.push(value >> (64 - shift));
+        }
+        let mut result = BigUint { limbs };
+        result.trim();
+        result
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.limbs.is_empty()
+    }
+
+    /// Number of bits without the leading zeros.
+    pub fn bits(&self) -> usize {
+        match self.limbs.last() {
+            Some(last) => self.limbs.len() * 64 - last.leading_zeros() as usize,
+            None => 0,
+        }
+    }
+
+    pub fn is_odd(&self) -> bool {
+        self.limbs.first().is_some_and(|limb| limb % 2 == 1)
+    }
+
+    pub fn increment(&mut self) {
+        for limb in self.limbs.iter_mut() {
+            let (result, overflow) = limb.overflowing_add(1);
+            *limb = result;
+            if !overflow {
+                return;
+            }
+        }
+        self.limbs.push(1);
+    }
+
+    /// Divides in place and returns the remainder.
+    pub fn div_rem_small(&mut self, divisor: u64) -> u64 {
+        let mut remainder = 0_u128;
+        for limb in self.limbs.iter_mut().rev() {
+            let current = (remainder << 64) | *limb as u128;
+            *limb = (current / divisor as u128) as u64;
+            remainder = current % divisor as u128;
+        }
+        self.trim();
+        remainder as u64
+    }
+
+    /// Binary long division, returning the quotient and the remainder.
+    pub fn div_rem(&self, divisor: &BigUint) -> (BigUint, BigUint) {
+        if let [small] = divisor.limbs[..] {
+            let mut quotient = self.clone();
+            let remainder = quotient.div_rem_small(small);
+            return (quotient, BigUint::from(remainder));
+        }
+
+        let mut quotient = BigUint {
+            limbs: vec![0; self.limbs.len()],
+        };
+        let mut remainder = BigUint::zero();
+        for bit in (0..self.limbs.len() * 64).rev() {
+            remainder.mul_small(2);
+            if self.limbs[bit / 64] >> (bit % 64) & 1 == 1 {
+                remainder.increment();
+            }
+            if remainder >= *divisor {
+                remainder.sub_assign(divisor);
+                quotient.limbs[bit / 64] |= 1 << (bit % 64);
+            }
+        }
+        quotient.trim();
+        (quotient, remainder)
+    }
+
+    pub fn mul_small(&mut self, factor: u64) {
+        let mut carry = 0_u128;
+        for limb in self.limbs.iter_mut() {
+            let current = *limb as u128 * factor as u128 + carry;
+            *limb = current as u64;
+            carry = current >> 64;
+        }
+        if carry > 0 {
+            self.limbs.push(carry as u64);
+        }
+        self.trim();
+    }
+
+    /// Schoolbook multiplication.
+    pub fn mul(&self, other: &BigUint) -> BigUint {
+        let mut limbs = vec![0; self.limbs.len() + other.limbs.len()];
+        for (i, a) in self.limbs.iter().enumerate() {
+            let mut carry = 0_u128;
+            for (j, b) in other.limbs.iter().enumerate() {
+                let current = *a as u128 * *b as u128 + limbs[i + j] as u128 + carry;
+                limbs[i + j] = current as u64;
+                carry = current >> 64;
+            }
+            limbs[i + other.limbs.len()] = carry as u64;
+        }
+        let mut result = BigUint { limbs };
+        result.trim();
+        result
+    }
+
+    /// Replaces `self` by `self % divisor` and returns `self / divisor`, which has to be small, like
+    /// a digit in long division.
+    pub fn div_rem_digit(&mut self, divisor: &BigUint) -> u64 {
+        debug_assert!(!divisor.is_zero(), "division by zero");
+        let mut quotient = 0;
+        while *self >= *divisor {
+            self.sub_assign(divisor);
+            quotient += 1;
+        }
+        quotient
+    }
+
+    /// The `bits` most significant bits, rounded half to even, and the power of 2 that scales them
+    /// back: `self ≈ mantissa * 2^exponent`. The last value tells whether that's exact.
+    pub fn to_mantissa(&self, bits: usize) -> (u64, i32, bool) {
+        let length = self.bits();
+        if length <= bits {
+            return (self.limbs.first().copied().unwrap_or(0), 0, true);
+        }
+
+        let shift = length - bits;
+        let mut mantissa = (shift..length)
+            .rev()
+            .fold(0, |acc, bit| acc << 1 | self.bit(bit));
+        let half = self.bit(shift - 1) == 1;
+        let sticky = (0..shift - 1).any(|bit| self.bit(bit) == 1);
+        if half && (sticky || mantissa % 2 == 1) {
+            mantissa += 1;
+        }
+        (mantissa, shift as i32, !half && !sticky)
+    }
+
+    pub fn to_str_radix(&self, radix: u32) -> String {
+        if self.is_zero() {
+            return "0".to_owned();
+        }
+
+        let mut value = self.clone();
+        let mut digits = Vec::new();
+        while !value.is_zero() {
+            let digit = value.div_rem_small(radix as u64) as u32;
+            digits.push(std::char::from_digit(digit, radix).unwrap());
+        }
+        digits.into_iter().rev().collect()
+    }
+
+    fn bit(&self, index: usize) -> u64 {
+        self.limbs[index / 64] >> (index % 64) & 1
+    }
+
+    /// `self - other`, where `other <= self`
+    fn sub_assign(&mut self, other: &BigUint) {
+        let mut borrow = false;
+        for (i, limb) in self.limbs.iter_mut().enumerate() {
+            let (result, overflow_a) =
+                limb.overflowing_sub(other.limbs.get(i).copied().unwrap_or(0));
+            let (result, overflow_b) = result.overflowing_sub(borrow as u64);
+            *limb = result;
+            borrow = overflow_a || overflow_b;
+        }
+        self.trim();
+    }
+
+    fn trim(&mut self) {
+        while self.limbs.last() == Some(&0) {
+            self.limbs.pop();
+        }
+    }
+}
+
+impl From<u64> for BigUint {
+    fn from(value: u64) -> Self {
+        BigUint::from_shifted(value, 0)
+    }
+}
+
+impl From<u128> for BigUint {
+    fn from(value: u128) -> Self {
+        let mut result = BigUint {
+            limbs: vec![value as u64, (value >> 64) as u64],
+        };
+        result.trim();
+        result
+    }
+}
+
+impl PartialOrd for BigUint {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for BigUint {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Without leading zeroes, more limbs means a bigger number.
+        self.limbs
+            .len()
+            .cmp(&other.limbs.len())
+            .then_with(|| self.limbs.iter().rev().cmp(other.limbs.iter().rev()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_prints_in_any_radix() {
+        assert_eq!(BigUint::zero().to_str_radix(10), "0");
+        assert_eq!(BigUint::from(0_u64).to_str_radix(10), "0");
+        assert_eq!(BigUint::from(255_u64).to_str_radix(16), "ff");
+        assert_eq!(
+            BigUint::from(u64::MAX).to_str_radix(10),
+            u64::MAX.to_string()
+        );
+        assert_eq!(
+            BigUint::from_shifted(1, 64).to_str_radix(10),
+            "18446744073709551616"
+        );
+        assert_eq!(
+            BigUint::from_shifted(3, 100).to_str_radix(10),
+            (3_u128 << 100).to_string()
+        );
+        assert_eq!(
+            BigUint::from_shifted(1, 200).to_str_radix(2),
+            "1".to_owned() + &"0".repeat(200)
+        );
+    }
+
+    #[test]
+    fn it_divides_with_remainder() {
+        let mut value = BigUint::from_shifted(1, 100);
+        assert_eq!(value.div_rem_small(3), ((1_u128 << 100) % 3) as u64);
+        assert_eq!(value.to_str_radix(10), ((1_u128 << 100) / 3).to_string());
+
+        let mut value = BigUint::from(7_u64);
+        assert_eq!(value.div_rem_small(8), 7);
+        assert!(value.is_zero());
+
+        let (quotient, remainder) = BigUint::from(u128::MAX).div_rem(&BigUint::from(3_u128 << 70));
+        assert_eq!(quotient, BigUint::from(u128::MAX / (3 << 70)));
+        assert_eq!(remainder, BigUint::from(u128::MAX % (3 << 70)));
+
+        let (quotient, remainder) = BigUint::from(5_u64).div_rem(&BigUint::from_shifted(1, 80));
+        assert!(quotient.is_zero());
+        assert_eq!(remainder, BigUint::from(5_u64));
+    }
+
+    #[test]
+    fn it_extracts_long_division_digits() {
+        let divisor = BigUint::from_shifted(7, 70);
+        let mut remainder = BigUint::from_shifted(1, 70);
+        let mut digits = Vec::new();
+        for _ in 0..6 {
+            remainder.mul_small(10);
+            digits.push(remainder.div_rem_digit(&divisor));
+        }
+        assert_eq!(digits, [1, 4, 2, 8, 5, 7]);
+        assert_eq!(remainder, BigUint::from_shifted(1, 70));
+    }
+
+    #[test]
+    fn it_compares() {
+        assert!(BigUint::from_shifted(1, 64) > BigUint::from(u64::MAX));
+        assert!(BigUint::from(u128::MAX) > BigUint::from_shifted(1, 127));
+        assert!(BigUint::zero() < BigUint::from(1_u64));
+        assert_eq!(BigUint::from(5_u128), BigUint::from(5_u64));
+    }
+
+    #[test]
+    fn it_counts_bits() {
+        assert_eq!(BigUint::zero().bits(), 0);
+        assert_eq!(BigUint::from(1_u64).bits(), 1);
+        assert_eq!(BigUint::from(255_u64).bits(), 8);
+        assert_eq!(BigUint::from_shifted(1, 64).bits(), 65);
+        assert_eq!(BigUint::from(u128::MAX).bits(), 128);
+    }
+
+    #[test]
+    fn it_multiplies() {
+        let value = BigUint::from(u64::MAX);
+        assert_eq!(
+            value.mul(&value),
+            BigUint::from(u64::MAX as u128 * u64::MAX as u128)
+        );
+        assert_eq!(
+            BigUint::from_shifted(3, 100).mul(&BigUint::from_shifted(5, 70)),
+            BigUint::from_shifted(15, 170)
+        );
+        assert!(value.mul(&BigUint::zero()).is_zero());
+    }
+
+    #[test]
+    fn it_takes_the_mantissa() {
+        assert_eq!(BigUint::zero().to_mantissa(53), (0, 0, true));
+        assert_eq!(BigUint::from(12345_u64).to_mantissa(53), (12345, 0, true));
+        assert_eq!(
+            BigUint::from_shifted(3, 200).to_mantissa(53),
+            (3 << 51, 149, true)
+        );
+        // 0b1011 rounds to 0b110 with 3 bits, 0b1001 to 0b100 and 0b1101 to 0b110.
+        assert_eq!(BigUint::from(0b1011_u64).to_mantissa(3), (0b110, 1, false));
+        assert_eq!(BigUint::from(0b1001_u64).to_mantissa(3), (0b100, 1, false));
+        assert_eq!(BigUint::from(0b1101_u64).to_mantissa(3), (0b110, 1, false));
+        assert_eq!(BigUint::from(0b1111_u64).to_mantissa(3), (0b1000, 1, false));
+        let value = BigUint::from_shifted(1, 100).mul(&BigUint::from(3_u64));
+        let mut above = value.clone();
+        above.increment();
+        assert_eq!(above.to_mantissa(2), (3, 100, false));
+    }
+
+    #[test]
+    fn it_increments() {
+        let mut value = BigUint::from(u64::MAX);
+        assert!(value.is_odd());
+        value.increment();
+        assert!(!value.is_odd());
+        assert_eq!(value, BigUint::from_shifted(1, 64));
+
+        let mut value = BigUint::zero();
+        value.increment();
+        assert_eq!(value, BigUint::from(1_u64));
+    }
+}
@@ -0,0 +1,140 @@
+use num_traits::{CheckedAdd, CheckedDiv, CheckedMul, CheckedRem, CheckedSub, Float};
+
+use crate::SafeDecimal;
+
+impl<T: Float> SafeDecimal<T> {
+    /// Adds two values, returning `None` if the result can't be represented exactly.
+    pub fn checked_add(self, rhs: Self) -> Option<Self> {
+        self.add_exact(rhs).ok()
+    }
+
+    /// Subtracts two values, returning `None` if the result can't be represented exactly.
+    pub fn checked_sub(self, rhs: Self) -> Option<Self> {
+        self.sub_exact(rhs).ok()
+    }
+
+    /// Multiplies two values, returning `None` if the result can't be represented exactly.
+    pub fn checked_mul(self, rhs: Self) -> Option<Self> {
+        self.mul_exact(rhs).ok()
+    }
+
+    /// Divides two values, returning `None` if `rhs` is zero or the result can't be
+    /// represented exactly.
+    pub fn checked_div(self, rhs: Self) -> Option<Self> {
+        self.mul_exact(rhs.inv()?).ok()
+    }
+
+    /// Truncated remainder, returning `None` if `rhs` is zero or the result can't be
+    /// represented exactly.
+    pub fn checked_rem(self, rhs: Self) -> Option<Self> {
+        if rhs.numerator == T::zero() {
+            return None;
+        }
+        self.rem_exact(rhs).ok()
+    }
+}
+
+impl<T: Float + std::fmt::Debug> CheckedAdd for SafeDecimal<T> {
+    fn checked_add(&self, v: &Self) -> Option<Self> {
+        SafeDecimal::checked_add(*self, *v)
+    }
+}
+
+impl<T: Float + std::fmt::Debug> CheckedSub for SafeDecimal<T> {
+    fn checked_sub(&self, v: &Self) -> Option<Self> {
+        SafeDecimal::checked_sub(*self, *v)
+    }
+}
+
+impl<T: Float> CheckedMul for SafeDecimal<T> {
+    fn checked_mul(&self, v: &Self) -> Option<Self> {
+        SafeDecimal::checked_mul(*self, *v)
+    }
+}
+
+impl<T: Float> CheckedDiv for SafeDecimal<T> {
+    fn checked_div(&self, v: &Self) -> Option<Self> {
+        SafeDecimal::checked_div(*self, *v)
+    }
+}
+
+impl<T: Float + std::fmt::Debug> CheckedRem for SafeDecimal<T> {
+    fn checked_rem(&self, v: &Self) -> Option<Self> {
+        SafeDecimal::checked_rem(*self, *v)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::d;
+
+    #[test]
+    fn it_returns_exact_results() {
+        assert_eq!(d(0.1).checked_add(d(0.2)), Some(d(0.3)));
+        assert_eq!(d(0.1).checked_sub(d(0.3)), Some(d(-0.2)));
+        assert_eq!(d(0.1).checked_mul(d(3.0)), Some(d(0.3)));
+        assert_eq!(d(1.0).checked_div(d(3.0)), Some(d(3.0).inv().unwrap()));
+        assert_eq!(d(-7.5).checked_rem(d(2.0)), Some(d(-1.5)));
+        assert_eq!(d(1e17).checked_rem(d(3.0)), Some(d(1.0)));
+    }
+
+    #[test]
+    fn it_rejects_division_by_zero() {
+        assert_eq!(d(1.0).checked_div(d(0.0)), None);
+        assert_eq!(d(1.0).checked_rem(d(0.0)), None);
+    }
+
+    #[test]
+    fn it_rejects_inexact_results() {
+        let big = d(9007199254740991.0); // 2^53 - 1
+        assert_eq!(big.checked_add(d(2.0)), None);
+        assert_eq!(big.checked_mul(d(3.0)), None);
+        assert!(big.checked_add(d(-1.0)).is_some());
+
+        // 1/3 + 1/5 + 1/7 + ... grows the denominator until it doesn't fit anymore
+        let mut value = d(0.0);
+        let mut prime = 3.0;
+        let overflowed = loop {
+            let term = d(prime).inv().unwrap();
+            match value.checked_add(term) {
+                Some(next) => value = next,
+                None => break true,
+            }
+            if prime > 100.0 {
+                break false;
+            }
+            prime += 2.0;
+        };
+        assert!(overflowed);
+    }
+
+    #[test]
+    fn it_rejects_non_finite_values() {
+        let nan = SafeDecimal {
+            numerator: f64::NAN,
+            denominator: 1.0,
+        };
+        let inf = SafeDecimal {
+            numerator: f64::INFINITY,
+            denominator: 1.0,
+        };
+        assert_eq!(nan.checked_add(d(1.0)), None);
+        assert_eq!(inf.checked_mul(d(1.0)), None);
+        let max = SafeDecimal {
+            numerator: f64::MAX,
+            denominator: 1.0,
+        };
+        assert_eq!(max.checked_mul(max), None);
+    }
+
+    #[test]
+    fn it_implements_num_traits() {
+        fn sum<T: CheckedAdd + Copy>(values: &[T]) -> Option<T> {
+            values[1..]
+                .iter()
+                .try_fold(values[0], |acc, v| acc.checked_add(v))
+        }
+        assert_eq!(sum(&[d(0.1), d(0.2), d(0.3)]), Some(d(0.6)));
+    }
+}
@@ -0,0 +1,314 @@
+use num_traits::Float;
+
+use crate::{format::Rounding, round::round_significant, Inexact, SafeDecimal};
+
+/// Exceptional conditions that can happen while operating through a [`Context`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Signal {
+    /// A non-zero value was divided by zero.
+    DivisionByZero,
+    /// The result had to be rounded, either to fit the precision of the context or because the
+    /// numerator or denominator couldn't hold it exactly.
+    Inexact,
+    /// The result is too large to be represented.
+    Overflow,
+}
+
+impl std::fmt::Display for Signal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Signal::DivisionByZero => write!(f, "division by zero"),
+            Signal::Inexact => write!(f, "the result was rounded"),
+            Signal::Overflow => write!(f, "the result overflowed"),
+        }
+    }
+}
+
+impl std::error::Error for Signal {}
+
+/// A set of [`Signal`]s.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Signals {
+    division_by_zero: bool,
+    inexact: bool,
+    overflow: bool,
+}
+
+impl Signals {
+    pub fn contains(&self, signal: Signal) -> bool {
+        *self.get(signal)
+    }
+    pub fn insert(&mut self, signal: Signal) {
+        *self.get_mut(signal) = true;
+    }
+    pub fn remove(&mut self, signal: Signal) {
+        *self.get_mut(signal) = false;
+    }
+    pub fn is_empty(&self) -> bool {
+        *self == Signals::default()
+    }
+
+    fn get(&self, signal: Signal) -> &bool {
+        match signal {
+            Signal::DivisionByZero => &self.division_by_zero,
+            Signal::Inexact => &self.inexact,
+            Signal::Overflow => &self.overflow,
+        }
+    }
+    fn get_mut(&mut self, signal: Signal) -> &mut bool {
+        match signal {
+            Signal::DivisionByZero => &mut self.division_by_zero,
+            Signal::Inexact => &mut self.inexact,
+            Signal::Overflow => &mut self.overflow,
+        }
+    }
+}
+
+/// Arithmetic environment: every operation done through the context gets rounded to its
+/// precision, and the signals raised along the way are recorded as flags.
+///
+/// A trapped signal makes the operation return it as an error. Otherwise the operation carries
+/// on with the rounded result, and results that don't fit saturate to the largest finite value
+/// with their sign.
+#[derive(Debug, Clone)]
+pub struct Context {
+    precision: Option<usize>,
+    rounding: Rounding,
+    traps: Signals,
+    flags: Signals,
+}
+
+impl Default for Context {
+    fn default() -> Self {
+        let mut traps = Signals::default();
+        traps.insert(Signal::DivisionByZero);
+        traps.insert(Signal::Overflow);
+
+        Self {
+            precision: None,
+            rounding: Rounding::HALF_EVEN,
+            traps,
+            flags: Signals::default(),
+        }
+    }
+}
+
+impl Context {
+    /// Maximum number of significant decimal digits of the results. `None` keeps them as they are.
+    pub fn precision(mut self, precision: Option<usize>) -> Self {
+        self.precision = precision;
+        self
+    }
+    pub fn rounding(mut self, rounding: Rounding) -> Self {
+        self.rounding = rounding;
+        self
+    }
+    pub fn trap(mut self, signal: Signal, enabled: bool) -> Self {
+        if enabled {
+            self.traps.insert(signal);
+        } else {
+            self.traps.remove(signal);
+        }
+        self
+    }
+
+    /// Signals raised since the context was created or the flags were last cleared.
+    pub fn flags(&self) -> Signals {
+        self.flags
+    }
+    pub fn clear_flags(&mut self) {
+        self.flags = Signals::default();
+    }
+
+    pub fn add<T: Float + std::fmt::Debug>(
+        &mut self,
+        a: SafeDecimal<T>,
+        b: SafeDecimal<T>,
+    ) -> Result<SafeDecimal<T>, Signal> {
+        self.apply(a.add_exact(b))
+    }
+
+    pub fn sub<T: Float + std::fmt::Debug>(
+        &mut self,
+        a: SafeDecimal<T>,
+        b: SafeDecimal<T>,
+    ) -> Result<SafeDecimal<T>, Signal> {
+        self.apply(a.sub_exact(b))
+    }
+
+    pub fn mul<T: Float + std::fmt::Debug>(
+        &mut self,
+        a: SafeDecimal<T>,
+        b: SafeDecimal<T>,
+    ) -> Result<SafeDecimal<T>, Signal> {
+        self.apply(a.mul_exact(b))
+    }
+
+    /// Division by zero results in the largest finite value with the sign of `a`, or zero when `a`
+    /// is zero, when it isn't trapped.
+    pub fn div<T: Float + std::fmt::Debug>(
+        &mut self,
+        a: SafeDecimal<T>,
+        b: SafeDecimal<T>,
+    ) -> Result<SafeDecimal<T>, Signal> {
+        match b.inv() {
+            Some(inv) => self.apply(a.mul_exact(inv)),
+            None => self.divide_by_zero(a),
+        }
+    }
+
+    /// Division by zero results in the largest finite value with the sign of `a`, or zero when `a`
+    /// is zero, when it isn't trapped.
+    pub fn rem<T: Float + std::fmt::Debug>(
+        &mut self,
+        a: SafeDecimal<T>,
+        b: SafeDecimal<T>,
+    ) -> Result<SafeDecimal<T>, Signal> {
+        if b.inv().is_none() {
+            return self.divide_by_zero(a);
+        }
+        self.apply(a.rem_exact(b))
+    }
+
+    /// Rounds a value to the precision of the context.
+    pub fn round<T: Float + std::fmt::Debug>(
+        &mut self,
+        value: SafeDecimal<T>,
+    ) -> Result<SafeDecimal<T>, Signal> {
+        self.apply(Ok(value))
+    }
+
+    fn apply<T: Float + std::fmt::Debug>(
+        &mut self,
+        result: Result<SafeDecimal<T>, Inexact<T>>,
+    ) -> Result<SafeDecimal<T>, Signal> {
+        let (value, mut inexact) = match result {
+            Ok(value) => (value, false),
+            Err(Inexact(value)) => (value, true),
+        };
+
+        if !value.numerator.is_finite() || !value.denominator.is_finite() {
+            self.raise(Signal::Overflow)?;
+            let is_negative =
+                value.numerator.is_sign_negative() != value.denominator.is_sign_negative();
+            return Ok(saturated(is_negative));
+        }
+
+        let value = match self.precision {
+            Some(precision) => {
+                let rounded = round_significant(value, precision, &self.rounding);
+                inexact |= rounded != value;
+                rounded
+            }
+            None => value,
+        };
+
+        if inexact {
+            self.raise(Signal::Inexact)?;
+        }
+        Ok(value)
+    }
+
+    fn divide_by_zero<T: Float>(&mut self, a: SafeDecimal<T>) -> Result<SafeDecimal<T>, Signal> {
+        self.raise(Signal::DivisionByZero)?;
+        if a.numerator == T::zero() {
+            return Ok(a);
+        }
+        Ok(saturated(
+            a.numerator.is_sign_negative() != a.denominator.is_sign_negative(),
+        ))
+    }
+
+    fn raise(&mut self, signal: Signal) -> Result<(), Signal> {
+        self.flags.insert(signal);
+        if self.traps.contains(signal) {
+            Err(signal)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// The largest finite value, which stands in for the infinities.
+pub fn saturated<T: Float>(is_negative: bool) -> SafeDecimal<T> {
+    let max = T::max_value();
+    SafeDecimal {
+        numerator: if is_negative { -max } else { max },
+        denominator: T::one(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::d;
+
+    #[test]
+    fn it_keeps_exact_results_by_default() {
+        let mut ctx = Context::default();
+        assert_eq!(ctx.add(d(0.1), d(0.2)), Ok(d(0.3)));
+        assert_eq!(ctx.div(d(1.0), d(3.0)), Ok(d(3.0).inv().unwrap()));
+        assert!(ctx.flags().is_empty());
+    }
+
+    #[test]
+    fn it_rounds_to_the_precision() {
+        let mut ctx = Context::default().precision(Some(4));
+        assert_eq!(ctx.div(d(2.0), d(3.0)), Ok(d(0.6667)));
+        assert!(ctx.flags().contains(Signal::Inexact));
+
+        ctx.clear_flags();
+        assert_eq!(ctx.mul(d(1.5), d(2.5)), Ok(d(3.75)));
+        assert!(ctx.flags().is_empty());
+
+        let mut ctx = Context::default()
+            .precision(Some(2))
+            .rounding(Rounding::FLOOR);
+        assert_eq!(ctx.sub(d(0.0), d(1.23)), Ok(d(-1.3)));
+    }
+
+    #[test]
+    fn it_traps_signals() {
+        let mut ctx = Context::default().precision(Some(4));
+        assert_eq!(ctx.div(d(1.0), d(0.0)), Err(Signal::DivisionByZero));
+        assert!(ctx.flags().contains(Signal::DivisionByZero));
+
+        let mut ctx = ctx.trap(Signal::Inexact, true);
+        assert_eq!(ctx.div(d(1.0), d(3.0)), Err(Signal::Inexact));
+        assert_eq!(ctx.round(d(0.5)), Ok(d(0.5)));
+
+        let big = d(9007199254740991.0); // 2^53 - 1
+        let mut ctx = Context::default().trap(Signal::Inexact, true);
+        assert_eq!(ctx.add(big, d(2.0)), Err(Signal::Inexact));
+    }
+
+    #[test]
+    fn it_flags_untrapped_signals() {
+        let mut ctx = Context::default().trap(Signal::DivisionByZero, false);
+        let result = ctx.div(d(-1.0), d(0.0)).unwrap();
+        assert_eq!(result.to_float(), -f64::MAX);
+        assert_eq!(ctx.rem(d(1.0), d(0.0)).unwrap().to_float(), f64::MAX);
+        assert_eq!(ctx.div(d(0.0), d(0.0)), Ok(d(0.0)));
+        assert!(ctx.flags().contains(Signal::DivisionByZero));
+        assert!(!ctx.flags().contains(Signal::Overflow));
+    }
+
+    #[test]
+    fn it_saturates_untrapped_results() {
+        let mut ctx = Context::default()
+            .trap(Signal::DivisionByZero, false)
+            .trap(Signal::Overflow, false);
+        let result = ctx.div(d(-1.0), d(0.0)).unwrap();
+        assert_eq!(result.to_string(), "-1.7976931348623157e308");
+        assert_eq!(format!("{:e}", result), "-1.7976931348623157e308");
+        assert_eq!(ctx.round(result), Ok(result));
+        assert_eq!(result.round_dp(2, Rounding::HALF_EVEN), result);
+
+        let max = SafeDecimal::new(f64::MAX, 1.0).unwrap();
+        let squared = ctx.mul(max, max).unwrap();
+        assert!(!ctx.flags().contains(Signal::Overflow));
+        assert_eq!(ctx.mul(squared, squared), Ok(max));
+        assert_eq!(ctx.mul(-squared, squared), Ok(-max));
+        assert!(ctx.flags().contains(Signal::Overflow));
+    }
+}
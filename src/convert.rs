@@ -34,43 +34,65 @@ pub fn from_f64<T: Float + LowerExp + std::fmt::Debug>(value: T) -> SafeDecimal<
      * we can't use numerical methods either because `123456.1 - 123456.0 != 0.1`, so we still need to work
      * with the string representation.
      */
-    let (integer_part, fractional_part) = get_integer_and_fraction(value);
-    from_parts(integer_part, fractional_part)
+    if value.is_normal() {
+        let (integer_part, fractional_part) = get_integer_and_fraction(value);
+        if let Some(parsed) = from_parts(integer_part, fractional_part) {
+            return parsed;
+        }
+    }
+
+    // Too many digits for the parser, so take the exact value of the float instead.
+    if let Ok(exact) = SafeDecimal::new(value, T::one()) {
+        return exact;
+    }
+    if value != T::zero() && value.is_finite() {
+        // Subnormal: shifted into the normal range, with the same shift in the denominator.
+        let scale = T::from(2).unwrap().powi(64);
+        return SafeDecimal::new(value * scale, scale).unwrap();
+    }
+    // Infinities and NaN stay as they are, like the results of operations that overflow.
+    SafeDecimal {
+        numerator: value,
+        denominator: T::one(),
+    }
 }
 
 fn from_parts<T: Float + std::fmt::Debug>(
     integer_part: String,
     fractional_part: String,
-) -> SafeDecimal<T> {
-    if fractional_part.len() == 0 {
-        return from_str_parts(&integer_part, "").unwrap();
+) -> Option<SafeDecimal<T>> {
+    if fractional_part.is_empty() {
+        return from_str_parts(&integer_part, "").ok();
     }
 
     // Check if inverting the fractional part gets rid of extra decimals.
     let inverted_fractional_part =
         1.0 / ("0.".to_owned() + &fractional_part).parse::<f64>().unwrap();
+    if !inverted_fractional_part.is_finite() {
+        return from_str_parts(&integer_part, &fractional_part).ok();
+    }
 
     let (inv_integer_part, inv_fractional_part) =
         get_integer_and_fraction(inverted_fractional_part);
 
     if inv_fractional_part.len() >= fractional_part.len() {
         // If it doesn't, then just return the value as it was.
-        return from_str_parts(&integer_part, &fractional_part).unwrap();
+        return from_str_parts(&integer_part, &fractional_part).ok();
     }
 
     // We have to ignore the negative symbol and add it at the end, because we're separating the integer from the fraction.
     let is_negative = integer_part.starts_with("-");
-    let integer_part_rational = from_integer(&integer_part).unwrap().abs();
+    let integer_part_rational = from_integer(&integer_part).ok()?.abs();
     let inv_fractional_part_rational =
-        from_parts(inv_integer_part.clone(), inv_fractional_part.clone());
+        from_parts(inv_integer_part.clone(), inv_fractional_part.clone())?;
 
     // Undoing the inversion - At this point all numbers are now represented as fractions, so it's safe to do these operations without losing precision.
-    let parsed = integer_part_rational + inv_fractional_part_rational.inv().unwrap();
+    let parsed = integer_part_rational + inv_fractional_part_rational.inv()?;
 
     if is_negative {
-        -parsed
+        Some(-parsed)
     } else {
-        parsed
+        Some(parsed)
     }
 }
 
@@ -84,7 +106,7 @@ fn get_integer_and_fraction<T: Float + LowerExp>(value: T) -> (String, String) {
         "".to_owned()
     };
     let numeric = numeric.replace(".", "").replace("-", "");
-    let exponent = isize::from_str_radix(exponent, 10).unwrap();
+    let exponent = exponent.parse::<isize>().unwrap();
 
     if exponent >= 0 {
         let max_int_pos = numeric.len().min(exponent as usize + 1);
@@ -128,6 +150,23 @@ mod tests {
         );
     }
 
+    #[test]
+    fn it_converts_values_of_any_magnitude() {
+        check_float_parsing(1e300);
+        check_float_parsing(-1e300);
+        check_float_parsing(f64::MAX);
+        check_float_parsing(1e-300);
+        check_float_parsing(1.5e-300);
+        check_float_parsing(f64::MIN_POSITIVE);
+        check_float_parsing(5e-324);
+        check_float_parsing(-1e-310);
+        check_float_parsing(f32::MAX);
+        check_float_parsing(1e-45_f32);
+        assert_eq!(from_f64(1e300), SafeDecimal::new(1e300, 1.0).unwrap());
+        assert_eq!(from_f64(f64::INFINITY).to_float(), f64::INFINITY);
+        assert!(from_f64(f64::NAN).to_float().is_nan());
+    }
+
     #[test]
     fn its_able_to_decode_simple_repeating_decimals() {
         check_num_den(
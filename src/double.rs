@@ -4,7 +4,7 @@ pub fn parse_double(value: f64) -> (u8, i16, u64) {
     let value_bits = value.to_bits();
 
     // Least 52 bits are mantissa
-    let mantissa = value_bits & 0x00_0F_FF_FF__FF_FF_FF_FF;
+    let mantissa = value_bits & 0x000F_FFFF_FFFF_FFFF;
     let value_bits = value_bits >> 52;
 
     // next 11 bits are exponent
@@ -104,7 +104,7 @@ pub fn from_exponential_from<T: Float>(sign: u8, value: u64, exp: i32) -> T {
 
     // And we also need to remove the leading 1
     let mask = !(1_u64 << mantissa_len);
-    return construct_float(sign, exp, (value << displacement) & mask);
+    construct_float(sign, exp, (value << displacement) & mask)
 }
 
 fn count_trailing_zeroes(value: u64) -> u8 {
@@ -136,7 +136,7 @@ fn count_trailing_zeroes(value: u64) -> u8 {
         result |= 32;
     }
 
-    return result;
+    result
 }
 
 fn count_leading_zeroes(value: u64) -> u8 {
@@ -152,10 +152,10 @@ fn count_leading_zeroes(value: u64) -> u8 {
     v = v | (v >> 16);
     v = v | (v >> 32);
 
-    return v.count_zeros() as u8;
+    v.count_zeros() as u8
 }
 
-fn is_f64<T: Float>() -> bool {
+pub fn is_f64<T: Float>() -> bool {
     T::min_value().to_f64().unwrap() == f64::min_value()
 }
 
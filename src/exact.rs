@@ -0,0 +1,193 @@
+use num_traits::Float;
+
+use crate::{
+    ops::{reduce_exponent, simplify_factors, truncated_remainder},
+    SafeDecimal,
+};
+
+/// Error returned by the `*_exact` operations when the result had to be rounded.
+///
+/// It still carries the rounded value, which is the same one the operator would have returned.
+#[derive(Debug, Clone, Copy)]
+pub struct Inexact<T>(pub SafeDecimal<T>);
+
+impl<T: Float> PartialEq for Inexact<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+impl<T: Float> Eq for Inexact<T> {}
+
+impl<T> Inexact<T> {
+    pub fn value(self) -> SafeDecimal<T> {
+        self.0
+    }
+}
+
+impl<T> std::fmt::Display for Inexact<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "the result of the operation can't be represented exactly"
+        )
+    }
+}
+
+impl<T: std::fmt::Debug> std::error::Error for Inexact<T> {}
+
+impl<T: Float> SafeDecimal<T> {
+    /// Same as `self + rhs`, but reports whether the result lost precision.
+    pub fn add_exact(self, rhs: Self) -> Result<Self, Inexact<T>> {
+        let mut exact = is_safe(&self) && is_safe(&rhs);
+
+        // Same steps as `Add`, but every float operation gets verified.
+        let (a, b) = simplify_factors(self.denominator, rhs.denominator);
+        exact &= a.is_normal() && b.is_normal();
+
+        let denominator = mul_tracked(self.denominator, b, &mut exact);
+        let numerator = add_tracked(
+            mul_tracked(self.numerator, b, &mut exact),
+            mul_tracked(rhs.numerator, a, &mut exact),
+            &mut exact,
+        );
+
+        reduce_tracked(numerator, denominator, exact)
+    }
+
+    /// Same as `self - rhs`, but reports whether the result lost precision.
+    pub fn sub_exact(self, rhs: Self) -> Result<Self, Inexact<T>> {
+        self.add_exact(-rhs)
+    }
+
+    /// Same as `self * rhs`, but reports whether the result lost precision.
+    pub fn mul_exact(self, rhs: Self) -> Result<Self, Inexact<T>> {
+        let mut exact = is_safe(&self) && is_safe(&rhs);
+
+        let (self_num, rhs_den) = simplify_factors(self.numerator, rhs.denominator);
+        let (rhs_num, self_den) = simplify_factors(rhs.numerator, self.denominator);
+        exact &= self_num.is_finite() && rhs_num.is_finite();
+
+        let numerator = mul_tracked(self_num, rhs_num, &mut exact);
+        let denominator = mul_tracked(self_den, rhs_den, &mut exact);
+
+        reduce_tracked(numerator, denominator, exact)
+    }
+
+    /// Same as `self / rhs`, but reports whether the result lost precision.
+    ///
+    /// # Panics
+    ///
+    /// If `rhs` is zero, like `self / rhs`. `checked_div` returns `None` instead.
+    pub fn div_exact(self, rhs: Self) -> Result<Self, Inexact<T>> {
+        self.mul_exact(rhs.inv().expect("Division by zero"))
+    }
+
+    /// Same as `self % rhs`, but reports whether the result lost precision.
+    ///
+    /// # Panics
+    ///
+    /// If `rhs` is zero, like `self % rhs`. `checked_rem` returns `None` instead.
+    pub fn rem_exact(self, rhs: Self) -> Result<Self, Inexact<T>> {
+        truncated_remainder(self, rhs)
+    }
+}
+
+/// Whether the components are something the operations can work with without losing precision:
+/// finite, with a positive denominator, and away from the subnormal range where the exponent
+/// manipulation in `double` doesn't hold.
+fn is_safe<T: Float>(value: &SafeDecimal<T>) -> bool {
+    (value.numerator == T::zero() || value.numerator.is_normal())
+        && value.denominator.is_normal()
+        && value.denominator > T::zero()
+}
+
+fn reduce_tracked<T: Float>(
+    numerator: T,
+    denominator: T,
+    exact: bool,
+) -> Result<SafeDecimal<T>, Inexact<T>> {
+    let result = reduce_exponent(SafeDecimal {
+        numerator,
+        denominator,
+    });
+    if exact && is_safe(&result) {
+        Ok(result)
+    } else {
+        Err(Inexact(result))
+    }
+}
+
+/// `a * b`, clearing `exact` if the product had to be rounded.
+fn mul_tracked<T: Float>(a: T, b: T, exact: &mut bool) -> T {
+    let product = a * b;
+    if product != T::zero() && !product.is_normal() {
+        *exact = false;
+    }
+
+    // fma computes `a * b - product` with a single rounding, which gives back the error of the product.
+    if a.mul_add(b, -product) != T::zero() {
+        *exact = false;
+    }
+    product
+}
+
+/// `a + b`, clearing `exact` if the sum had to be rounded.
+fn add_tracked<T: Float>(a: T, b: T, exact: &mut bool) -> T {
+    let sum = a + b;
+    if !sum.is_finite() {
+        *exact = false;
+    }
+
+    // Knuth's TwoSum: recovers the error of the sum.
+    let b_virtual = sum - a;
+    let a_virtual = sum - b_virtual;
+    let error = (a - a_virtual) + (b - b_virtual);
+    if error != T::zero() {
+        *exact = false;
+    }
+    sum
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::d;
+
+    #[test]
+    fn it_returns_exact_results() {
+        assert_eq!(d(0.1).add_exact(d(0.2)), Ok(d(0.3)));
+        assert_eq!(d(0.1).sub_exact(d(0.3)), Ok(d(-0.2)));
+        assert_eq!(d(0.1).mul_exact(d(3.0)), Ok(d(0.3)));
+        assert_eq!(d(1.0).div_exact(d(3.0)), Ok(d(3.0).inv().unwrap()));
+        assert_eq!(d(-7.5).rem_exact(d(2.0)), Ok(d(-1.5)));
+        assert_eq!(d(1e17).rem_exact(d(3.0)), Ok(d(1.0)));
+    }
+
+    #[test]
+    fn it_reports_rounded_results() {
+        let big = d(9007199254740991.0); // 2^53 - 1
+
+        let result = big.add_exact(d(2.0));
+        assert_eq!(result, Err(Inexact(big + d(2.0))));
+        assert_eq!(result.unwrap_err().value().to_float(), 9007199254740992.0);
+
+        assert_eq!(big.mul_exact(d(3.0)), Err(Inexact(big * d(3.0))));
+        assert!(big.sub_exact(d(1.0)).is_ok());
+
+        let f32_big = SafeDecimal::from(16777215.0_f32); // 2^24 - 1
+        assert!(f32_big.add_exact(SafeDecimal::from(2.0_f32)).is_err());
+        assert!(f32_big.add_exact(SafeDecimal::from(-2.0_f32)).is_ok());
+    }
+
+    #[test]
+    #[should_panic(expected = "Division by zero")]
+    fn it_panics_on_division_by_zero() {
+        let _ = d(1.0).div_exact(d(0.0));
+    }
+
+    #[test]
+    #[should_panic(expected = "Division by zero")]
+    fn it_panics_on_remainder_by_zero() {
+        let _ = d(1.0).rem_exact(d(0.0));
+    }
+}
@@ -1,64 +1,144 @@
-use std::cmp::Ordering;
+use std::{
+    cmp::Ordering,
+    collections::HashMap,
+    fmt,
+    sync::{
+        atomic::{AtomicU64, Ordering as AtomicOrdering},
+        Arc,
+    },
+};
 
 use num_traits::Float;
 
-use crate::SafeDecimal;
+use crate::{
+    big_uint::BigUint,
+    context::saturated,
+    double::{exponential_form, is_f64},
+    ops::balanced,
+    parsing::parse_separated,
+    Inexact, SafeDecimal,
+};
 
-#[derive(Debug, Clone, Copy)]
-enum RoundingDirection {
+/// Which way to round a discarded fraction, or only ties when [`Rounding`] rounds to the nearest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RoundingDirection {
+    /// Away from zero.
     Up,
+    /// Towards zero.
     Down,
+    /// Towards positive infinity.
     Ceil,
+    /// Towards negative infinity.
     Floor,
+    /// Towards an even last digit.
     Even,
+    /// Towards an odd last digit, which makes a later rounding to fewer digits give the same result
+    /// as rounding just once.
+    Odd,
+    /// Towards zero, unless the last digit would be 0 or 5. Same as Python's `ROUND_05UP`.
+    ZeroFiveUp,
+    /// Away from zero with a probability equal to the discarded fraction, so that the rounding
+    /// errors average out. The seed makes the sequence reproducible, and clones of the same
+    /// [`Rounding`] continue a single sequence.
+    Stochastic(u64),
 }
 
 #[derive(Debug, Clone)]
 pub struct Rounding {
     direction: RoundingDirection,
     nearest: bool,
+    /// State of the random generator for [`RoundingDirection::Stochastic`], shared between clones.
+    random: Option<Arc<AtomicU64>>,
 }
 
 impl Rounding {
+    /// Rounds towards `direction`, or to the nearest value and only towards `direction` on ties when
+    /// `nearest` is set. For instance, `Rounding::new(RoundingDirection::Odd, true)` rounds half to
+    /// odd.
+    pub fn new(direction: RoundingDirection, nearest: bool) -> Self {
+        let random = match direction {
+            RoundingDirection::Stochastic(seed) => Some(Arc::new(AtomicU64::new(seed))),
+            _ => None,
+        };
+        Rounding {
+            direction,
+            nearest,
+            random,
+        }
+    }
+
+    /// Shorthand for `Rounding::new(RoundingDirection::Stochastic(seed), false)`.
+    ///
+    /// Clones share the state of the random generator, so they all draw from the same sequence.
+    /// Use roundings with different seeds to get independent sequences.
+    pub fn stochastic(seed: u64) -> Self {
+        Rounding::new(RoundingDirection::Stochastic(seed), false)
+    }
+
     pub const UP: Rounding = Rounding {
         direction: RoundingDirection::Up,
         nearest: false,
+        random: None,
     };
     pub const DOWN: Rounding = Rounding {
         direction: RoundingDirection::Down,
         nearest: false,
+        random: None,
     };
     pub const CEIL: Rounding = Rounding {
         direction: RoundingDirection::Ceil,
         nearest: false,
+        random: None,
     };
     pub const FLOOR: Rounding = Rounding {
         direction: RoundingDirection::Floor,
         nearest: false,
+        random: None,
     };
     pub const EVEN: Rounding = Rounding {
         direction: RoundingDirection::Even,
         nearest: false,
+        random: None,
     };
     pub const HALF_UP: Rounding = Rounding {
         direction: RoundingDirection::Up,
         nearest: true,
+        random: None,
     };
     pub const HALF_DOWN: Rounding = Rounding {
         direction: RoundingDirection::Down,
         nearest: true,
+        random: None,
     };
     pub const HALF_CEIL: Rounding = Rounding {
         direction: RoundingDirection::Ceil,
         nearest: true,
+        random: None,
     };
     pub const HALF_FLOOR: Rounding = Rounding {
         direction: RoundingDirection::Floor,
         nearest: true,
+        random: None,
     };
     pub const HALF_EVEN: Rounding = Rounding {
         direction: RoundingDirection::Even,
         nearest: true,
+        random: None,
+    };
+    pub const ODD: Rounding = Rounding {
+        direction: RoundingDirection::Odd,
+        nearest: false,
+        random: None,
+    };
+    pub const HALF_ODD: Rounding = Rounding {
+        direction: RoundingDirection::Odd,
+        nearest: true,
+        random: None,
+    };
+    pub const ZERO_FIVE_UP: Rounding = Rounding {
+        direction: RoundingDirection::ZeroFiveUp,
+        nearest: false,
+        random: None,
     };
 }
 
@@ -68,32 +148,174 @@ pub enum Radix {
     Octal,
     Decimal,
     Hexadecimal,
+    /// Any radix from 2 to 36, with digits `0-9` followed by `a-z`.
+    Custom(u32),
 }
 
 impl Radix {
-    fn value(&self) -> f64 {
+    fn value(&self) -> u32 {
         match self {
-            Radix::Binary => 2.0,
-            Radix::Octal => 8.0,
-            Radix::Decimal => 10.0,
-            Radix::Hexadecimal => 16.0,
+            Radix::Binary => 2,
+            Radix::Octal => 8,
+            Radix::Decimal => 10,
+            Radix::Hexadecimal => 16,
+            Radix::Custom(radix) => *radix,
         }
     }
-    fn format(&self, integer: u64) -> String {
+    fn prefix(&self) -> &'static str {
         match self {
-            Radix::Binary => format!("{:b}", integer),
-            Radix::Octal => format!("{:o}", integer),
-            Radix::Decimal => format!("{}", integer),
-            Radix::Hexadecimal => format!("{:x}", integer),
+            Radix::Binary => "0b",
+            Radix::Octal => "0o",
+            Radix::Decimal | Radix::Custom(_) => "",
+            Radix::Hexadecimal => "0x",
         }
     }
 }
 
+/// How to mark the repeating digits of a fraction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RepetendStyle {
+    /// `0.1(6)`
+    Parentheses,
+    /// `0.16̅`, with a combining overline on every repeating digit.
+    Overline,
+    /// `0.1666…`
+    Ellipsis,
+}
+
+impl RepetendStyle {
+    fn format(&self, digits: &[u8], start: usize) -> String {
+        let non_repeating = digits[..start].iter().map(|d| char_to_str(*d));
+        let repeating = digits[start..].iter().map(|d| char_to_str(*d));
+
+        match self {
+            RepetendStyle::Parentheses => non_repeating
+                .chain(std::iter::once('('))
+                .chain(repeating)
+                .chain(std::iter::once(')'))
+                .collect(),
+            RepetendStyle::Overline => non_repeating
+                .chain(repeating.flat_map(|d| [d, '\u{305}']))
+                .collect(),
+            RepetendStyle::Ellipsis => {
+                // Enough repetitions that it's obvious which digits repeat. The parser takes the longest
+                // period that shows up twice at the end, which these always are.
+                let copies = if digits.len() - start == 1 { 3 } else { 2 };
+                non_repeating
+                    .chain(repeating.cycle().take((digits.len() - start) * copies))
+                    .chain(std::iter::once('…'))
+                    .collect()
+            }
+        }
+    }
+}
+
+/// How to lay out the digits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Notation {
+    /// `602000000000000000000000`
+    Positional,
+    /// `6.02e23`, with a single non-zero digit before the point.
+    Scientific,
+    /// `602e21`, with an exponent that is a multiple of 3.
+    Engineering,
+}
+
+/// How to write the exponent in scientific and engineering notation. The exponent is a power of the
+/// radix, written in decimal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExponentStyle {
+    /// `6.02e23`
+    LowerE,
+    /// `6.02E23`
+    UpperE,
+    /// `6.02×10^23`, where `10` is the radix written in itself.
+    TimesTenPower,
+}
+
+impl ExponentStyle {
+    fn format(&self, exponent: i32) -> String {
+        match self {
+            ExponentStyle::LowerE => format!("e{}", exponent),
+            ExponentStyle::UpperE => format!("E{}", exponent),
+            ExponentStyle::TimesTenPower => format!("×10^{}", exponent),
+        }
+    }
+}
+
+/// The decimal mark and how to group the integer digits, like `1,234,567.89`, `1.234.567,89` or
+/// `12,34,567.89`. The parser accepts the same settings.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Separators {
+    pub(crate) decimal: char,
+    pub(crate) group: Option<char>,
+    pub(crate) group_size: usize,
+    pub(crate) secondary_group_size: usize,
+}
+
+impl Default for Separators {
+    fn default() -> Self {
+        Self {
+            decimal: '.',
+            group: None,
+            group_size: 3,
+            secondary_group_size: 3,
+        }
+    }
+}
+
+impl Separators {
+    pub fn decimal(mut self, separator: char) -> Self {
+        self.decimal = separator;
+        self
+    }
+    /// Groups the integer digits with this separator, which has to be different from the decimal mark.
+    pub fn group(mut self, separator: char) -> Self {
+        self.group = Some(separator);
+        self
+    }
+    /// Size of the group next to the decimal mark, and of the ones after it. Indian grouping, like
+    /// `12,34,567`, is `group_sizes(3, 2)`. Neither can be 0.
+    pub fn group_sizes(mut self, primary: usize, secondary: usize) -> Self {
+        self.group_size = primary;
+        self.secondary_group_size = secondary;
+        self
+    }
+
+    fn group_digits(&self, integer: &str) -> String {
+        let separator = match self.group {
+            Some(separator) if integer.len() > self.group_size => separator,
+            _ => return integer.to_owned(),
+        };
+
+        let (rest, primary) = integer.split_at(integer.len() - self.group_size);
+        let mut groups = vec![primary];
+        let mut rest = rest;
+        while !rest.is_empty() {
+            let (head, group) = rest.split_at(rest.len().saturating_sub(self.secondary_group_size));
+            groups.push(group);
+            rest = head;
+        }
+        groups.reverse();
+        groups.join(&separator.to_string())
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct FormatOptions {
     radix: Radix,
     max_decimals: usize,
     rounding: Rounding,
+    repetend: Option<RepetendStyle>,
+    max_period: usize,
+    prefix: bool,
+    min_decimals: usize,
+    trailing_zeros: bool,
+    separators: Separators,
+    significant_digits: Option<usize>,
+    notation: Notation,
+    exponent_style: ExponentStyle,
+    shortest: bool,
 }
 
 impl Default for FormatOptions {
@@ -102,10 +324,21 @@ impl Default for FormatOptions {
             radix: Radix::Decimal,
             max_decimals: 16,
             rounding: Rounding::HALF_CEIL,
+            repetend: None,
+            max_period: 32,
+            prefix: false,
+            min_decimals: 0,
+            trailing_zeros: false,
+            separators: Separators::default(),
+            significant_digits: None,
+            notation: Notation::Positional,
+            exponent_style: ExponentStyle::LowerE,
+            shortest: false,
         }
     }
 }
 impl FormatOptions {
+    /// A `Radix::Custom` has to be between 2 and 36.
     pub fn radix(mut self, radix: Radix) -> Self {
         self.radix = radix;
         self
@@ -114,103 +347,743 @@ impl FormatOptions {
         self.max_decimals = max_decimals;
         self
     }
+    /// Pads the decimals with zeros up to this length, even past `max_decimals`.
+    pub fn min_decimals(mut self, min_decimals: usize) -> Self {
+        self.min_decimals = min_decimals;
+        self
+    }
+    /// Exactly `decimals` decimals, like `12.50` for a price.
+    pub fn fixed(self, decimals: usize) -> Self {
+        self.min_decimals(decimals).max_decimals(decimals)
+    }
+    /// Keeps the zeros at the end of the decimals instead of trimming them, like in `2.00` when
+    /// `1.999` gets rounded to 2 decimals.
+    pub fn trailing_zeros(mut self, keep: bool) -> Self {
+        self.trailing_zeros = keep;
+        self
+    }
     pub fn rounding(mut self, rounding: Rounding) -> Self {
         self.rounding = rounding;
         self
     }
+    /// Marks the repeating digits with the given style, instead of rounding them at `max_decimals`.
+    ///
+    /// The digits before the repetend still need to fit in `max_decimals`, and the repetend in
+    /// `max_period`. Otherwise it falls back to rounding.
+    pub fn repetend(mut self, style: RepetendStyle) -> Self {
+        self.repetend = Some(style);
+        self
+    }
+    pub fn max_period(mut self, max_period: usize) -> Self {
+        self.max_period = max_period;
+        self
+    }
+    /// Prepends `0b`, `0o` or `0x` for non-decimal radices, the same prefixes the parser understands.
+    pub fn prefix(mut self, prefix: bool) -> Self {
+        self.prefix = prefix;
+        self
+    }
+    /// Rounds to this many significant digits instead of using `max_decimals`, and shows all of
+    /// them, like `0.0001230` or `1.230e5`. It switches to exponent notation when the positional one
+    /// would need zeros that aren't significant, or more than 6 leading zeros. `digits` can't be 0.
+    pub fn significant_digits(mut self, digits: usize) -> Self {
+        self.significant_digits = Some(digits);
+        self
+    }
+    pub fn separators(mut self, separators: Separators) -> Self {
+        self.separators = separators;
+        self
+    }
+    /// In scientific and engineering notation, `max_decimals` and the repetend apply to the mantissa.
+    pub fn notation(mut self, notation: Notation) -> Self {
+        self.notation = notation;
+        self
+    }
+    pub fn exponent_style(mut self, exponent_style: ExponentStyle) -> Self {
+        self.exponent_style = exponent_style;
+        self
+    }
+    /// Writes the fewest significant digits that parse back to the same value, like `0.1` instead of
+    /// `0.1000000000000000`, instead of using `max_decimals` and the rounding. Values with repeating
+    /// digits get their repetend, like `0.(3)`, as long as it fits in `max_decimals` and
+    /// `max_period`. Otherwise, and for values that no decimal parses back to, it writes the
+    /// fraction, such as `1/97`.
+    ///
+    /// It switches to exponent notation below 1e-6 and from 1e21 on. It only applies to decimal
+    /// numbers, which are the ones `FromStr` reads with an exponent.
+    ///
+    /// Finding the digits means parsing every candidate back, which makes it about 10 times slower
+    /// than writing `max_decimals` digits. `Display` uses it, so prefer `to_decimal` or the
+    /// precision of the formatter, like `{:.2}`, when formatting many values.
+    pub fn shortest(mut self, shortest: bool) -> Self {
+        self.shortest = shortest;
+        self
+    }
+
+    /// Checks the settings that can't be used to format, which makes `Formatted` return
+    /// `fmt::Error` and `SafeDecimal::to_decimal` panic.
+    pub fn validate(&self) -> Result<(), FormatOptionsError> {
+        if let Radix::Custom(radix) = self.radix {
+            if !(2..=36).contains(&radix) {
+                return Err(FormatOptionsError::UnsupportedRadix(radix));
+            }
+        }
+        if self.separators.group_size == 0 || self.separators.secondary_group_size == 0 {
+            return Err(FormatOptionsError::ZeroGroupSize);
+        }
+        if self.separators.group == Some(self.separators.decimal) {
+            return Err(FormatOptionsError::GroupIsDecimal);
+        }
+        if self.significant_digits == Some(0) {
+            return Err(FormatOptionsError::ZeroSignificantDigits);
+        }
+        Ok(())
+    }
+}
+
+/// Error returned by [`FormatOptions::validate`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FormatOptionsError {
+    /// A custom radix isn't between 2 and 36.
+    UnsupportedRadix(u32),
+    /// A group size of the separators is 0.
+    ZeroGroupSize,
+    /// The group separator is the same as the decimal mark, which makes the output ambiguous.
+    GroupIsDecimal,
+    /// The number of significant digits is 0.
+    ZeroSignificantDigits,
+}
+
+impl fmt::Display for FormatOptionsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FormatOptionsError::UnsupportedRadix(radix) => {
+                write!(f, "radix {} is not between 2 and 36", radix)
+            }
+            FormatOptionsError::ZeroGroupSize => write!(f, "group sizes can't be 0"),
+            FormatOptionsError::GroupIsDecimal => {
+                write!(f, "the group separator can't be the decimal mark")
+            }
+            FormatOptionsError::ZeroSignificantDigits => {
+                write!(f, "there has to be at least 1 significant digit")
+            }
+        }
+    }
+}
+
+impl std::error::Error for FormatOptionsError {}
+
+/// A value together with the options to format it, to use with `format!` and friends.
+///
+/// The precision of the formatter overrides `max_decimals`. Width, fill, alignment and the `+` and
+/// `0` flags work the same as they do for integers, and `#` shows the radix prefix.
+#[derive(Debug, Clone)]
+pub struct Formatted<'a, T> {
+    value: &'a SafeDecimal<T>,
+    options: FormatOptions,
+}
+
+impl<'a, T> Formatted<'a, T> {
+    pub fn new(value: &'a SafeDecimal<T>, options: FormatOptions) -> Self {
+        Formatted { value, options }
+    }
 }
 
-pub fn to_decimal<T: Float + PartialOrd>(
+impl<T: Float + fmt::Debug> fmt::Display for Formatted<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.options.validate().map_err(|_| fmt::Error)?;
+        let options = match f.precision() {
+            Some(precision) => self.options.clone().shortest(false).max_decimals(precision),
+            None => self.options.clone(),
+        };
+        let (is_negative, digits) = to_parts(self.value, &options);
+        let prefix = options.radix.prefix();
+        if options.prefix && !f.alternate() {
+            return pad_with_prefix(f, is_negative, prefix, &digits);
+        }
+        f.pad_integral(!is_negative, prefix, &digits)
+    }
+}
+
+/// Like `Formatter::pad_integral` in alternate mode, which is the only mode it writes the prefix
+/// in, so the `prefix` option goes between the sign and the zero padding too.
+fn pad_with_prefix(
+    f: &mut fmt::Formatter<'_>,
+    is_negative: bool,
+    prefix: &str,
+    digits: &str,
+) -> fmt::Result {
+    let sign = if is_negative {
+        "-"
+    } else if f.sign_plus() {
+        "+"
+    } else {
+        ""
+    };
+    let length = sign.len() + prefix.len() + digits.chars().count();
+    let padding = f.width().unwrap_or(0).saturating_sub(length);
+    if f.sign_aware_zero_pad() {
+        write!(f, "{}{}{}{}", sign, prefix, "0".repeat(padding), digits)?;
+        return Ok(());
+    }
+    let (before, after) = match f.align() {
+        Some(fmt::Alignment::Left) => (0, padding),
+        Some(fmt::Alignment::Center) => (padding / 2, padding - padding / 2),
+        Some(fmt::Alignment::Right) | None => (padding, 0),
+    };
+    let fill = f.fill().to_string();
+    write!(
+        f,
+        "{}{}{}{}{}",
+        fill.repeat(before),
+        sign,
+        prefix,
+        digits,
+        fill.repeat(after)
+    )
+}
+
+/// Formatting traits round half to even, like floats do.
+fn trait_options() -> FormatOptions {
+    FormatOptions::default().rounding(Rounding::HALF_EVEN)
+}
+
+macro_rules! impl_fmt {
+    ($trait:ident, $options:expr) => {
+        impl<T: Float + fmt::Debug> fmt::$trait for SafeDecimal<T> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                fmt::Display::fmt(&Formatted::new(self, $options), f)
+            }
+        }
+    };
+}
+
+impl_fmt!(Display, trait_options().shortest(true));
+impl_fmt!(LowerHex, trait_options().radix(Radix::Hexadecimal));
+impl_fmt!(Binary, trait_options().radix(Radix::Binary));
+impl_fmt!(Octal, trait_options().radix(Radix::Octal));
+impl_fmt!(
+    LowerExp,
+    trait_options()
+        .notation(Notation::Scientific)
+        .exponent_style(ExponentStyle::LowerE)
+);
+impl_fmt!(
+    UpperExp,
+    trait_options()
+        .notation(Notation::Scientific)
+        .exponent_style(ExponentStyle::UpperE)
+);
+
+pub fn to_decimal<T: Float + fmt::Debug>(
     value: &SafeDecimal<T>,
     options: &FormatOptions,
 ) -> String {
-    let sign = if value.numerator < num_traits::zero() {
-        "-"
+    let (is_negative, digits) = to_parts(value, options);
+    let sign = if is_negative { "-" } else { "" };
+    let prefix = if options.prefix {
+        options.radix.prefix()
     } else {
         ""
     };
-    let mut numerator = value.numerator.abs();
-    let denominator = value.denominator;
+    format!("{}{}{}", sign, prefix, digits)
+}
+
+/// Whether it needs a minus sign, and the digits that go after the sign and the prefix.
+fn to_parts<T: Float + fmt::Debug>(
+    value: &SafeDecimal<T>,
+    options: &FormatOptions,
+) -> (bool, String) {
+    let value = &match finite(value) {
+        Some(value) => value,
+        None => return (false, "NaN".to_owned()),
+    };
+    if options.shortest && matches!(options.radix, Radix::Decimal) {
+        return shortest(value, options);
+    }
+
+    let is_negative = (value.numerator < T::zero()) != (value.denominator < T::zero());
+    let (numerator, denominator) = to_integers(value.numerator, value.denominator);
+
+    let (integer, decimals, exponent) = match options.notation {
+        _ if options.significant_digits.is_some() => {
+            let digits = options.significant_digits.unwrap();
+            significant(
+                is_negative,
+                &numerator,
+                &denominator,
+                options,
+                digits,
+                digits as i32,
+            )
+        }
+        Notation::Positional => {
+            let (integer, decimals) = positional(is_negative, &numerator, &denominator, options);
+            (integer, decimals, String::new())
+        }
+        Notation::Scientific | Notation::Engineering => {
+            let (integer, decimals, exponent) =
+                exponential(is_negative, &numerator, &denominator, options);
+            (integer, decimals, options.exponent_style.format(exponent))
+        }
+    };
+    join_parts(is_negative, integer, decimals, exponent, options)
+}
+
+/// The value with finite sides and a denominator that isn't zero, which is what the digits are
+/// computed from. Overflowed values saturate like they do in a `Context`, and undefined ones like
+/// `0/0` are `None`.
+fn finite<T: Float>(value: &SafeDecimal<T>) -> Option<SafeDecimal<T>> {
+    if value.numerator.is_finite()
+        && value.denominator.is_finite()
+        && value.denominator != T::zero()
+    {
+        return Some(*value);
+    }
+    let quotient = value.numerator / value.denominator;
+    if quotient.is_nan() {
+        return None;
+    }
+    if quotient.is_infinite() {
+        return Some(saturated(quotient < T::zero()));
+    }
+    // A finite numerator over an infinite denominator
+    Some(SafeDecimal {
+        numerator: T::zero(),
+        denominator: T::one(),
+    })
+}
+
+fn join_parts(
+    is_negative: bool,
+    integer: String,
+    decimals: String,
+    exponent: String,
+    options: &FormatOptions,
+) -> (bool, String) {
+    // Avoid sign when it rounds to zero
+    let is_zero = (integer.clone() + &decimals).chars().all(|c| c == '0');
+    let mut digits = options.separators.group_digits(&integer);
+    if !decimals.is_empty() {
+        digits.push(options.separators.decimal);
+        digits += &decimals;
+    }
+    (is_negative && !is_zero, digits + &exponent)
+}
+
+/// The shortest digits that parse back to `value`: the fewest significant digits, or the repetend
+/// when they don't terminate, or else the fraction.
+fn shortest<T: Float + fmt::Debug>(
+    value: &SafeDecimal<T>,
+    options: &FormatOptions,
+) -> (bool, String) {
+    let options = options.clone().rounding(Rounding::HALF_EVEN);
+    let is_negative = (value.numerator < T::zero()) != (value.denominator < T::zero());
+    let (numerator, denominator) = to_integers(value.numerator, value.denominator);
+
+    let found = match exact_digits(&numerator, &denominator) {
+        Some(digits) => shortest_digits(value, &options, digits),
+        None => {
+            let repetend = options.clone().repetend(RepetendStyle::Parentheses);
+            let (integer, decimals) = positional(is_negative, &numerator, &denominator, &repetend);
+            Some(join_parts(
+                is_negative,
+                integer,
+                decimals,
+                String::new(),
+                &options,
+            ))
+            .filter(|parts| round_trips(value, parts, &options))
+        }
+    };
+    if let Some(parts) = found {
+        return parts;
+    }
+
+    // Both sides are written with all their digits, which is the integer they really are even
+    // when a shorter number would parse to the same float. The parser only reads integers of up to
+    // 128 bits, so larger ones go in exponent notation, with their digits just as exact.
+    let side = |integer: &BigUint| {
+        let digits = integer.to_str_radix(10);
+        if *integer <= BigUint::from(u128::MAX) {
+            return options.separators.group_digits(&digits);
+        }
+        let significant = digits.trim_end_matches('0');
+        let mut mantissa = significant[..1].to_owned();
+        if significant.len() > 1 {
+            mantissa.push(options.separators.decimal);
+            mantissa += &significant[1..];
+        }
+        mantissa + &options.exponent_style.format(digits.len() as i32 - 1)
+    };
+    (is_negative, side(&numerator) + "/" + &side(&denominator))
+}
+
+/// Above this many significant digits the parser rounds to the closest float, so there's no point
+/// in trying more before the exact digits.
+const MAX_SHORTEST_DIGITS: usize = 40;
+
+/// The fewest significant digits that parse back to `value`, which takes `exact_digits` to write
+/// exactly.
+fn shortest_digits<T: Float + fmt::Debug>(
+    value: &SafeDecimal<T>,
+    options: &FormatOptions,
+    exact_digits: usize,
+) -> Option<(bool, String)> {
+    (1..=exact_digits.min(MAX_SHORTEST_DIGITS))
+        .chain(Some(exact_digits).filter(|digits| *digits > MAX_SHORTEST_DIGITS))
+        .map(|digits| significant_parts(value, options, digits))
+        .find(|parts| round_trips(value, parts, options))
+}
+
+fn significant_parts<T: Float>(
+    value: &SafeDecimal<T>,
+    options: &FormatOptions,
+    digits: usize,
+) -> (bool, String) {
+    let is_negative = (value.numerator < T::zero()) != (value.denominator < T::zero());
+    let (numerator, denominator) = to_integers(value.numerator, value.denominator);
+    let max_positional = match options.notation {
+        Notation::Positional => 21,
+        _ => 0,
+    };
+    let (integer, decimals, exponent) = significant(
+        is_negative,
+        &numerator,
+        &denominator,
+        options,
+        digits,
+        max_positional,
+    );
+    join_parts(is_negative, integer, decimals, exponent, options)
+}
+
+fn round_trips<T: Float + fmt::Debug>(
+    value: &SafeDecimal<T>,
+    (is_negative, digits): &(bool, String),
+    options: &FormatOptions,
+) -> bool {
+    let sign = if *is_negative { "-" } else { "" };
+    parse_separated::<T>(&(sign.to_owned() + digits), &options.separators, false)
+        .is_ok_and(|parsed| parsed == *value)
+}
+
+/// How many significant digits it takes to write `numerator / denominator` exactly, if they
+/// terminate.
+fn exact_digits(numerator: &BigUint, denominator: &BigUint) -> Option<usize> {
+    if numerator.is_zero() {
+        return Some(1);
+    }
+    let (integer, remainder) = numerator.div_rem(denominator);
+    let decimals = fraction_length(remainder, denominator)?;
+    if integer.is_zero() {
+        // Without the leading zeros, like the 3 decimals of 0.005
+        let exponent = magnitude(numerator, denominator, 10);
+        return Some((decimals as i32 + exponent + 1) as usize);
+    }
+    Some(integer.to_str_radix(10).len() + decimals)
+}
 
-    let mut integer_part = (numerator / denominator).trunc().to_u64().unwrap();
+/// How many decimals it takes to write `remainder / divisor` exactly, if it terminates.
+fn fraction_length(mut remainder: BigUint, divisor: &BigUint) -> Option<usize> {
+    // Every digit removes a factor of 10 from the divisor, so more digits than bits means that it
+    // has other factors and never terminates.
+    let limit = divisor.bits();
+    let mut length = 0;
+    while !remainder.is_zero() {
+        if length > limit {
+            return None;
+        }
+        remainder.mul_small(10);
+        remainder.div_rem_digit(divisor);
+        length += 1;
+    }
+    Some(length)
+}
 
-    // numerator and denominator are both safe.
-    // multiplying an integer by a safe number gives a safe number.
-    // subtracting two safe numbers also gives a safe number.
-    numerator = numerator - T::from(integer_part).unwrap() * denominator;
+/// `|numerator / denominator|` as a fraction of integers.
+///
+/// Both components are `mantissa * 2^exp`, so it only takes shifting the mantissas. The result can
+/// be far bigger than any primitive integer.
+pub fn to_integers<T: Float>(numerator: T, denominator: T) -> (BigUint, BigUint) {
+    let (_, n_mantissa, n_exp) = exponential_form(numerator);
+    let (_, d_mantissa, d_exp) = exponential_form(denominator);
+    let shift = n_exp - d_exp;
+    (
+        BigUint::from_shifted(n_mantissa, shift.max(0) as u32),
+        BigUint::from_shifted(d_mantissa, (-shift).max(0) as u32),
+    )
+}
+
+/// `numerator / denominator` as a normalized fraction of floats, the inverse of `to_integers`. It's
+/// exact as long as both integers fit in the mantissa once shifted, and the value is in range.
+pub fn from_integers<T: Float>(
+    numerator: &BigUint,
+    denominator: &BigUint,
+) -> Result<SafeDecimal<T>, Inexact<T>> {
+    let bits = if is_f64::<T>() { 53 } else { 24 };
+    let (n_int, n_exp, n_exact) = numerator.to_mantissa(bits);
+    let (d_int, d_exp, d_exact) = denominator.to_mantissa(bits);
+    if n_int == 0 {
+        return Ok(SafeDecimal {
+            numerator: T::zero(),
+            denominator: T::one(),
+        });
+    }
+
+    let value = balanced::<T>(0, (n_int, n_exp), (d_int, d_exp)).normalize();
+    if n_exact && d_exact && value.numerator.is_normal() {
+        Ok(value)
+    } else {
+        Err(Inexact(value))
+    }
+}
+
+/// Integer and fractional digits of `numerator / denominator`, without sign.
+fn positional(
+    is_negative: bool,
+    numerator: &BigUint,
+    denominator: &BigUint,
+    options: &FormatOptions,
+) -> (String, String) {
+    let radix = options.radix.value();
+    let (mut integer_part, mut remainder) = numerator.div_rem(denominator);
+
+    if let Some(style) = &options.repetend {
+        if let Some((digits, start)) = repeating_digits(remainder.clone(), denominator, options) {
+            return (
+                integer_part.to_str_radix(radix),
+                style.format(&digits, start),
+            );
+        }
+    }
 
     let mut decimal_part = Vec::with_capacity(options.max_decimals);
     for _ in 0..options.max_decimals {
-        if numerator == num_traits::zero() {
+        if remainder.is_zero() {
             break;
         }
 
         // Multiply a/b by radix, extract integer, repeat.
-        // TODO radix :)
-        numerator = numerator * T::from(10).unwrap();
-        let div_result = (numerator / denominator).trunc();
-        decimal_part.push(div_result.to_u8().unwrap());
-
-        numerator = numerator - div_result * denominator;
+        remainder.mul_small(radix as u64);
+        decimal_part.push(remainder.div_rem_digit(denominator) as u8);
     }
 
-    if numerator != num_traits::zero() {
-        let is_negative = sign == "-";
-        let is_odd = options.max_decimals == 0 && integer_part % 2 == 1
-            || decimal_part[decimal_part.len() - 1] % 2 == 1;
-        let half_cmp = (T::from(2).unwrap() * numerator)
-            .partial_cmp(&denominator)
-            .unwrap_or(Ordering::Equal);
+    if !remainder.is_zero() {
+        let last_digit = match decimal_part.last() {
+            Some(digit) => *digit as u64,
+            None => integer_part.clone().div_rem_small(radix as u64),
+        };
 
         if options
             .rounding
-            .should_increment(is_negative, is_odd, half_cmp)
+            .should_increment(is_negative, last_digit, &remainder, denominator)
+            && increment(&mut decimal_part, &options.radix)
         {
-            integer_part += increment(&mut decimal_part, &options.radix);
+            integer_part.increment();
         }
     }
 
-    // TODO radix
-    let decimal_str = decimal_part
+    let mut decimal_str = decimal_part
         .into_iter()
-        .map(|d| char_to_str(d))
-        .collect::<String>()
-        .trim_end_matches('0')
-        .to_owned();
+        .map(char_to_str)
+        .collect::<String>();
+    if !options.trailing_zeros {
+        decimal_str.truncate(decimal_str.trim_end_matches('0').len());
+    }
+    while decimal_str.len() < options.min_decimals {
+        decimal_str.push('0');
+    }
 
-    if integer_part == 0 && decimal_str.len() == 0 {
-        // Avoid sign
-        return "0".to_owned();
+    (integer_part.to_str_radix(radix), decimal_str)
+}
+
+/// Integer and fractional digits of the mantissa of `numerator / denominator` and its exponent,
+/// without sign.
+fn exponential(
+    is_negative: bool,
+    numerator: &BigUint,
+    denominator: &BigUint,
+    options: &FormatOptions,
+) -> (String, String, i32) {
+    if numerator.is_zero() {
+        let (integer, decimals) = positional(is_negative, numerator, denominator, options);
+        return (integer, decimals, 0);
     }
 
-    let decimal_str = if decimal_str.len() > 0 {
-        ".".to_owned() + &decimal_str
+    let radix = options.radix.value() as u64;
+    let mut exponent = magnitude(numerator, denominator, radix);
+    loop {
+        let shown = match options.notation {
+            Notation::Engineering => exponent - exponent.rem_euclid(3),
+            _ => exponent,
+        };
+        let (numerator, denominator) = scale(numerator, denominator, shown, radix);
+        let (integer, decimals) = positional(is_negative, &numerator, &denominator, options);
+        // Rounding up can carry into a new digit, like 9.99 becoming 10.0
+        if integer.len() as i32 > exponent - shown + 1 {
+            exponent += 1;
+            continue;
+        }
+        return (integer, decimals, shown);
+    }
+}
+
+/// Rounds to `digits` significant digits, in positional notation unless the exponent is below -6
+/// or from `max_positional` on.
+///
+/// Returns the integer and fractional digits, and the exponent if there's one.
+fn significant(
+    is_negative: bool,
+    numerator: &BigUint,
+    denominator: &BigUint,
+    options: &FormatOptions,
+    digits: usize,
+    max_positional: i32,
+) -> (String, String, String) {
+    let radix = options.radix.value() as u64;
+    let mut exponent = if numerator.is_zero() {
+        0
     } else {
-        decimal_str
+        magnitude(numerator, denominator, radix)
     };
 
-    return format!(
-        "{}{}{}",
-        sign,
-        options.radix.format(integer_part),
-        decimal_str
-    );
+    loop {
+        let use_exponent =
+            options.notation != Notation::Positional || exponent < -6 || exponent >= max_positional;
+        let shown = match options.notation {
+            _ if !use_exponent => 0,
+            Notation::Engineering => exponent - exponent.rem_euclid(3),
+            _ => exponent,
+        };
+        // Negative when the last significant digit is left of the point, like 2 digits of 123e0.
+        let decimals = digits as i32 - 1 - (exponent - shown);
+
+        let (numerator, denominator) =
+            scale(numerator, denominator, shown - decimals.min(0), radix);
+        let (integer, fraction) = positional(
+            is_negative,
+            &numerator,
+            &denominator,
+            &options.clone().fixed(decimals.max(0) as usize),
+        );
+        // Rounding up can carry into a new digit, like 9.99 becoming 10.0
+        if (integer.clone() + &fraction).trim_start_matches('0').len() > digits {
+            exponent += 1;
+            continue;
+        }
+
+        let integer = if integer != "0" {
+            integer + &"0".repeat(decimals.min(0).unsigned_abs() as usize)
+        } else {
+            integer
+        };
+        let exponent = if use_exponent {
+            options.exponent_style.format(shown)
+        } else {
+            String::new()
+        };
+        return (integer, fraction, exponent);
+    }
+}
+
+/// `numerator / denominator / radix^exponent`
+fn scale(
+    numerator: &BigUint,
+    denominator: &BigUint,
+    exponent: i32,
+    radix: u64,
+) -> (BigUint, BigUint) {
+    let (mut numerator, mut denominator) = (numerator.clone(), denominator.clone());
+    for _ in 0..exponent.unsigned_abs() {
+        if exponent > 0 {
+            denominator.mul_small(radix);
+        } else {
+            numerator.mul_small(radix);
+        }
+    }
+    (numerator, denominator)
+}
+
+/// `floor(log_radix(numerator / denominator))`, for a non-zero value.
+fn magnitude(numerator: &BigUint, denominator: &BigUint, radix: u64) -> i32 {
+    let (integer, _) = numerator.div_rem(denominator);
+    if !integer.is_zero() {
+        return integer.to_str_radix(radix as u32).len() as i32 - 1;
+    }
+
+    let mut scaled = numerator.clone();
+    let mut exponent = 0;
+    while scaled < *denominator {
+        scaled.mul_small(radix);
+        exponent -= 1;
+    }
+    exponent
+}
+
+/// Long division of a proper fraction, looking for a remainder that has already shown up.
+///
+/// Returns the digits up to the end of the first repetend and the position where it starts, or
+/// `None` if the fraction terminates or the repetend doesn't fit in the options.
+fn repeating_digits(
+    mut remainder: BigUint,
+    divisor: &BigUint,
+    options: &FormatOptions,
+) -> Option<(Vec<u8>, usize)> {
+    let radix = options.radix.value() as u64;
+    let mut seen = HashMap::new();
+    let mut digits = Vec::new();
+
+    while digits.len() <= options.max_decimals + options.max_period {
+        if remainder.is_zero() {
+            return None;
+        }
+        if let Some(start) = seen.insert(remainder.clone(), digits.len()) {
+            let period = digits.len() - start;
+            if start > options.max_decimals || period > options.max_period {
+                return None;
+            }
+            return Some((digits, start));
+        }
+
+        remainder.mul_small(radix);
+        digits.push(remainder.div_rem_digit(divisor) as u8);
+    }
+    None
 }
 
-fn increment(decimals: &mut Vec<u8>, radix: &Radix) -> u64 {
+/// Adds one to the last digit, returning whether it carries over to the integer part.
+fn increment(decimals: &mut [u8], radix: &Radix) -> bool {
     for d in (0..decimals.len()).rev() {
         if decimals[d] < radix.value() as u8 - 1 {
             decimals[d] += 1;
-            return 0;
+            return false;
         }
         decimals[d] = 0;
     }
-    return 1;
+    true
 }
+impl Rounding {
+    /// Whether to move the truncated value one unit away from zero, given its last digit and the
+    /// discarded fraction `remainder / denominator`, which can't be zero.
+    pub(crate) fn should_increment(
+        &self,
+        is_negative: bool,
+        last_digit: u64,
+        remainder: &BigUint,
+        denominator: &BigUint,
+    ) -> bool {
+        if self.nearest {
+            let mut doubled = remainder.clone();
+            doubled.mul_small(2);
+            match doubled.cmp(denominator) {
+                Ordering::Less => return false,
+                Ordering::Greater => return true,
+                Ordering::Equal => {}
+            }
+        }
 
-impl RoundingDirection {
-    fn should_increment(&self, is_negative: bool, is_odd: bool) -> bool {
-        match self {
+        match self.direction {
             RoundingDirection::Up => {
                 // <- 0 ->
                 true
@@ -227,32 +1100,828 @@ impl RoundingDirection {
                 // <- Infinity
                 is_negative
             }
-            RoundingDirection::Even => {
-                // <- Infinity
-                is_odd
+            RoundingDirection::Even => last_digit % 2 == 1,
+            RoundingDirection::Odd => last_digit % 2 == 0,
+            RoundingDirection::ZeroFiveUp => last_digit == 0 || last_digit == 5,
+            RoundingDirection::Stochastic(_) => {
+                // random / 2^64 < remainder / denominator
+                let mut threshold = remainder.clone();
+                threshold.mul_small(1 << 32);
+                threshold.mul_small(1 << 32);
+                let mut scaled = denominator.clone();
+                scaled.mul_small(self.next_random());
+                scaled < threshold
             }
         }
     }
-}
 
-impl Rounding {
-    fn should_increment(&self, is_negative: bool, is_odd: bool, half_cmp: Ordering) -> bool {
-        if !self.nearest {
-            return self.direction.should_increment(is_negative, is_odd);
-        }
-
-        match half_cmp {
-            Ordering::Equal => self.direction.should_increment(is_negative, is_odd),
-            Ordering::Less => false,
-            Ordering::Greater => true,
-        }
+    /// SplitMix64, which is enough to spread the rounding errors and only needs an atomic add.
+    fn next_random(&self) -> u64 {
+        const GAMMA: u64 = 0x9e3779b97f4a7c15;
+        let state = match &self.random {
+            Some(random) => random.fetch_add(GAMMA, AtomicOrdering::Relaxed),
+            None => 0,
+        };
+        let mut z = state.wrapping_add(GAMMA);
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
     }
 }
 
 fn char_to_str(num: u8) -> char {
     if num >= 10 {
-        ('a' as u8 + (num - 10)) as char
+        (b'a' + (num - 10)) as char
     } else {
-        ('0' as u8 + num) as char
+        (b'0' + num) as char
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frac(numerator: f64, denominator: f64) -> SafeDecimal<f64> {
+        SafeDecimal::new(numerator, denominator).unwrap()
+    }
+
+    fn parsed(value: &str) -> SafeDecimal<f64> {
+        value.parse().unwrap()
+    }
+
+    #[test]
+    fn it_formats_fractional_digits_in_radix() {
+        let hex = FormatOptions::default().radix(Radix::Hexadecimal);
+        assert_eq!(to_decimal(&frac(1.0, 2.0), &hex), "0.8");
+        assert_eq!(to_decimal(&frac(-2815.0, 256.0), &hex), "-a.ff");
+        assert_eq!(to_decimal(&frac(1.0, 10.0), &hex), "0.199999999999999a");
+
+        let binary = FormatOptions::default().radix(Radix::Binary);
+        assert_eq!(to_decimal(&frac(7.0, 4.0), &binary), "1.11");
+        assert_eq!(
+            to_decimal(&frac(1.0, 3.0), &binary.max_decimals(4)),
+            "0.0101"
+        );
+
+        let octal = FormatOptions::default().radix(Radix::Octal);
+        assert_eq!(to_decimal(&frac(9.0, 8.0), &octal), "1.1");
+        assert_eq!(to_decimal(&frac(1.0, 7.0), &octal), "0.1111111111111111");
+    }
+
+    #[test]
+    fn it_rounds_in_radix() {
+        let hex = FormatOptions::default()
+            .radix(Radix::Hexadecimal)
+            .max_decimals(1);
+        // 0x0.f8 is exactly halfway between 0x0.f and 0x1
+        assert_eq!(to_decimal(&frac(248.0, 256.0), &hex), "1");
+        let hex = hex.rounding(Rounding::HALF_EVEN);
+        assert_eq!(to_decimal(&frac(248.0, 256.0), &hex), "1");
+        assert_eq!(to_decimal(&frac(232.0, 256.0), &hex), "0.e");
+        assert_eq!(
+            to_decimal(&frac(2.5, 1.0), &hex.clone().max_decimals(0)),
+            "2"
+        );
+        assert_eq!(to_decimal(&frac(3.5, 1.0), &hex.max_decimals(0)), "4");
+    }
+
+    #[test]
+    fn it_formats_with_custom_rounding() {
+        let two = FormatOptions::default().max_decimals(2);
+        let half_odd = two.clone().rounding(Rounding::HALF_ODD);
+        assert_eq!(to_decimal(&frac(1.0, 8.0), &half_odd), "0.13");
+        assert_eq!(to_decimal(&frac(3.0, 8.0), &half_odd), "0.37");
+        assert_eq!(to_decimal(&frac(-3.0, 8.0), &half_odd), "-0.37");
+        let zero_five_up = two.clone().rounding(Rounding::ZERO_FIVE_UP);
+        assert_eq!(to_decimal(&frac(1.0, 3.0), &zero_five_up), "0.33");
+        assert_eq!(to_decimal(&frac(1.0, 1000.0), &zero_five_up), "0.01");
+        let ceil = two.rounding(Rounding::new(RoundingDirection::Ceil, false));
+        assert_eq!(to_decimal(&frac(1.0, 3.0), &ceil), "0.34");
+    }
+
+    #[test]
+    fn it_formats_radix_prefixes() {
+        let options = FormatOptions::default().prefix(true);
+        assert_eq!(to_decimal(&frac(-2815.0, 256.0), &options), "-10.99609375");
+        assert_eq!(
+            to_decimal(
+                &frac(-2815.0, 256.0),
+                &options.clone().radix(Radix::Hexadecimal)
+            ),
+            "-0xa.ff"
+        );
+        assert_eq!(
+            to_decimal(&frac(5.0, 2.0), &options.clone().radix(Radix::Binary)),
+            "0b10.1"
+        );
+        assert_eq!(
+            to_decimal(&frac(0.0, 1.0), &options.clone().radix(Radix::Octal)),
+            "0o0"
+        );
+
+        for radix in [Radix::Binary, Radix::Octal, Radix::Hexadecimal] {
+            let options = options.clone().radix(radix);
+            for value in [
+                frac(-2815.0, 256.0),
+                frac(3.0, 1024.0),
+                frac(48879.125, 1.0),
+            ] {
+                let formatted = to_decimal(&value, &options);
+                let parsed: SafeDecimal<f64> = formatted.parse().unwrap();
+                assert_eq!(to_decimal(&parsed, &options), formatted);
+            }
+        }
+    }
+
+    #[test]
+    fn it_formats_integer_parts_beyond_u64() {
+        let options = FormatOptions::default();
+        assert_eq!(
+            to_decimal(&SafeDecimal::from(1e30), &options),
+            "1000000000000000019884624838656"
+        );
+        assert_eq!(
+            to_decimal(&frac(f64::MAX, 1.0), &options),
+            format!("{:.0}", f64::MAX)
+        );
+        assert_eq!(
+            to_decimal(&frac(f64::MIN, 1.0), &options),
+            format!("{:.0}", f64::MIN)
+        );
+        assert_eq!(
+            to_decimal(&SafeDecimal::new(f32::MAX, 1.0).unwrap(), &options),
+            format!("{:.0}", f32::MAX)
+        );
+        // Way beyond what a float can hold.
+        assert_eq!(
+            to_decimal(
+                &frac(f64::MAX, 2f64.powi(-1000)),
+                &options.clone().radix(Radix::Hexadecimal)
+            ),
+            // 2^2024 - 2^1971
+            "f".repeat(13) + "8" + &"0".repeat(492)
+        );
+        assert_eq!(
+            to_decimal(&frac(2f64.powi(100), 3.0), &options.clone().max_decimals(4)),
+            format!("{}.3333", (1_u128 << 100) / 3)
+        );
+    }
+
+    #[test]
+    fn it_formats_overflowed_values() {
+        let options = FormatOptions::default();
+        let max = frac(f64::MAX, 1.0);
+        let squared = max * max;
+        let overflowed = squared * squared;
+        assert_eq!(
+            to_decimal(&overflowed, &options),
+            format!("{:.0}", f64::MAX)
+        );
+        assert_eq!(
+            to_decimal(&-overflowed, &options),
+            format!("{:.0}", f64::MIN)
+        );
+        assert_eq!(
+            to_decimal(&overflowed, &options.clone().notation(Notation::Scientific)),
+            to_decimal(&max, &options.clone().notation(Notation::Scientific))
+        );
+        let undefined = SafeDecimal {
+            numerator: 0.0,
+            denominator: 0.0,
+        };
+        assert_eq!(to_decimal(&undefined, &options), "NaN");
+        assert_eq!(overflowed.to_string(), "1.7976931348623157e308");
+        assert_eq!(format!("{}", -overflowed), "-1.7976931348623157e308");
+        assert_eq!(undefined.to_string(), "NaN");
+    }
+
+    #[test]
+    fn it_formats_fractional_digits_exactly() {
+        let options = FormatOptions::default();
+        assert_eq!(
+            to_decimal(&frac(9007199254740991.0, 3.0), &options),
+            "3002399751580330.3333333333333333"
+        );
+        let tiny = 2f64.powi(-80);
+        assert_eq!(
+            to_decimal(&frac(tiny, 1.0), &options.clone().max_decimals(40)),
+            format!("{:.40}", tiny).trim_end_matches('0')
+        );
+        assert_eq!(
+            to_decimal(&frac(1.0, f64::MAX), &options.clone().max_decimals(40)),
+            "0"
+        );
+        assert_eq!(
+            to_decimal(
+                &frac(1.0, 3.0 * 2f64.powi(100)),
+                &options
+                    .repetend(RepetendStyle::Parentheses)
+                    .max_decimals(40)
+            ),
+            to_decimal(
+                &frac(1.0, 3.0 * 2f64.powi(100)),
+                &FormatOptions::default().max_decimals(40)
+            )
+        );
+    }
+
+    #[test]
+    fn it_formats_scientific_notation() {
+        let options = FormatOptions::default().notation(Notation::Scientific);
+        assert_eq!(to_decimal(&parsed("6.02e23"), &options), "6.02e23");
+        assert_eq!(to_decimal(&parsed("1.6e-19"), &options), "1.6e-19");
+        assert_eq!(to_decimal(&parsed("-1234.5"), &options), "-1.2345e3");
+        assert_eq!(to_decimal(&parsed("7"), &options), "7e0");
+        assert_eq!(to_decimal(&parsed("0"), &options), "0e0");
+        assert_eq!(
+            to_decimal(&frac(f64::MAX, 1.0), &options),
+            format!("{:e}", f64::MAX)
+        );
+        assert_eq!(
+            to_decimal(&frac(f64::MIN_POSITIVE, 1.0), &options),
+            format!("{:e}", f64::MIN_POSITIVE)
+        );
+        assert_eq!(
+            to_decimal(&frac(1.0, 3.0), &options.clone().max_decimals(3)),
+            "3.333e-1"
+        );
+        assert_eq!(
+            to_decimal(
+                &frac(1.0, 3.0),
+                &options.clone().repetend(RepetendStyle::Parentheses)
+            ),
+            "3.(3)e-1"
+        );
+        assert_eq!(
+            to_decimal(
+                &frac(256.0, 1.0),
+                &options.clone().radix(Radix::Hexadecimal)
+            ),
+            "1e2"
+        );
+    }
+
+    #[test]
+    fn it_rounds_scientific_notation() {
+        let options = FormatOptions::default()
+            .notation(Notation::Scientific)
+            .max_decimals(1);
+        assert_eq!(to_decimal(&parsed("9.99"), &options), "1e1");
+        assert_eq!(to_decimal(&parsed("-0.0999"), &options), "-1e-1");
+        assert_eq!(
+            to_decimal(&parsed("-1.25"), &options.clone().rounding(Rounding::FLOOR)),
+            "-1.3e0"
+        );
+        assert_eq!(
+            to_decimal(
+                &parsed("-1.25"),
+                &options.clone().rounding(Rounding::HALF_EVEN)
+            ),
+            "-1.2e0"
+        );
+
+        let options = options.notation(Notation::Engineering).max_decimals(0);
+        assert_eq!(to_decimal(&parsed("999.9"), &options), "1e3");
+        assert_eq!(to_decimal(&parsed("99.9"), &options), "100e0");
+    }
+
+    #[test]
+    fn it_formats_engineering_notation() {
+        let options = FormatOptions::default().notation(Notation::Engineering);
+        assert_eq!(to_decimal(&parsed("1.5e20"), &options), "150e18");
+        assert_eq!(to_decimal(&parsed("1.6e-19"), &options), "160e-21");
+        assert_eq!(to_decimal(&parsed("12345"), &options), "12.345e3");
+        assert_eq!(to_decimal(&parsed("0.5"), &options), "500e-3");
+        assert_eq!(to_decimal(&parsed("1"), &options), "1e0");
+    }
+
+    #[test]
+    fn it_formats_exponent_styles() {
+        let value = "6.02e23".parse::<SafeDecimal<f64>>().unwrap();
+        let options = FormatOptions::default().notation(Notation::Scientific);
+        assert_eq!(
+            to_decimal(
+                &value,
+                &options.clone().exponent_style(ExponentStyle::UpperE)
+            ),
+            "6.02E23"
+        );
+        assert_eq!(
+            to_decimal(
+                &value,
+                &options.clone().exponent_style(ExponentStyle::TimesTenPower)
+            ),
+            "6.02×10^23"
+        );
+        assert_eq!(
+            to_decimal(
+                &value.inv().unwrap(),
+                &options
+                    .exponent_style(ExponentStyle::TimesTenPower)
+                    .max_decimals(3)
+            ),
+            "1.661×10^-24"
+        );
+    }
+
+    #[test]
+    fn it_implements_display() {
+        assert_eq!(format!("{}", parsed("-12.5")), "-12.5");
+        assert_eq!(parsed("-12.5").to_string(), "-12.5");
+        assert_eq!(format!("{}", frac(1.0, 3.0)), "0.(3)");
+        assert_eq!(format!("{:.2}", parsed("2.675")), "2.68");
+        assert_eq!(format!("{:.2}", parsed("2.665")), "2.66");
+        assert_eq!(format!("{:.0}", parsed("-0.5")), "0");
+        assert_eq!(format!("{:+.0}", parsed("-0.5")), "+0");
+        assert_eq!(format!("{:.1}", parsed("-0.06")), "-0.1");
+    }
+
+    #[test]
+    fn it_formats_the_shortest_representation() {
+        let shortest = FormatOptions::default().shortest(true);
+        let format = |value: &SafeDecimal<f64>| to_decimal(value, &shortest);
+        assert_eq!(format(&parsed("0")), "0");
+        assert_eq!(format(&parsed("1")), "1");
+        assert_eq!(format(&parsed("-2.5")), "-2.5");
+        assert_eq!(format(&parsed("0.1")), "0.1");
+        assert_eq!(format(&parsed("123.4567890123")), "123.4567890123");
+        // The float closest to 0.1 isn't 1/10, which is what "0.1" parses to
+        assert_eq!(
+            format(&frac(0.1, 1.0)),
+            "3602879701896397/36028797018963968"
+        );
+        assert_eq!(format(&frac(2f64.powi(-60), 1.0)), "8.673617379884035e-19");
+        assert_eq!(format(&parsed("1e20")), "100000000000000000000");
+        assert_eq!(format(&parsed("1e21")), "1e21");
+        assert_eq!(format(&parsed("1.5e-7")), "1.5e-7");
+        assert_eq!(format(&parsed("0.0000015")), "0.0000015");
+        assert_eq!(format(&frac(-1.0, 3.0)), "-0.(3)");
+        assert_eq!(format(&frac(1.0, 7.0)), "0.(142857)");
+        assert_eq!(format(&frac(1.0, 97.0)), "1/97");
+        assert_eq!(
+            to_decimal(
+                &parsed("12345.5"),
+                &shortest.clone().notation(Notation::Scientific)
+            ),
+            "1.23455e4"
+        );
+        let f32_value = "0.1".parse::<SafeDecimal<f32>>().unwrap();
+        assert_eq!(to_decimal(&f32_value, &shortest), "0.1");
+        assert_eq!(
+            to_decimal(&SafeDecimal::new(0.1_f32, 1.0).unwrap(), &shortest),
+            "13421773/134217728"
+        );
+
+        for value in [
+            frac(0.1, 1.0),
+            frac(1.0, 97.0),
+            frac(1e300, 97.0),
+            frac(f64::MAX, 3.0),
+            frac(1.0, 3.0 * 2f64.powi(200)),
+            frac(1.0, 3.0),
+            frac(1e-30, 3.0),
+            frac(-1e300, 7.0),
+            frac(2f64.powi(-1000), 1.0),
+            frac(f64::MAX, 1.0),
+            frac(123.456, 1000.0),
+            parsed("-98765.4321"),
+        ] {
+            let printed = value.to_string();
+            assert_eq!(printed.parse::<SafeDecimal<f64>>(), Ok(value), "{printed}");
+        }
+    }
+
+    #[test]
+    fn it_pads_decimals() {
+        let options = FormatOptions::default();
+        assert_eq!(
+            to_decimal(&parsed("12.5"), &options.clone().fixed(2)),
+            "12.50"
+        );
+        assert_eq!(to_decimal(&parsed("3"), &options.clone().fixed(3)), "3.000");
+        assert_eq!(
+            to_decimal(&parsed("3.14159"), &options.clone().fixed(3)),
+            "3.142"
+        );
+        assert_eq!(to_decimal(&parsed("0"), &options.clone().fixed(2)), "0.00");
+        assert_eq!(
+            to_decimal(&parsed("-0.001"), &options.clone().fixed(2)),
+            "0.00"
+        );
+        assert_eq!(
+            to_decimal(&parsed("12.345"), &options.clone().min_decimals(2)),
+            "12.345"
+        );
+        assert_eq!(
+            to_decimal(
+                &parsed("12.5"),
+                &options.clone().min_decimals(4).max_decimals(2)
+            ),
+            "12.5000"
+        );
+        assert_eq!(
+            to_decimal(
+                &SafeDecimal::from(0.5),
+                &options.clone().radix(Radix::Binary).fixed(3)
+            ),
+            "0.100"
+        );
+        assert_eq!(
+            to_decimal(
+                &parsed("1.5e6"),
+                &options.notation(Notation::Scientific).fixed(2)
+            ),
+            "1.50e6"
+        );
+    }
+
+    #[test]
+    fn it_keeps_trailing_zeros_after_rounding() {
+        let options = FormatOptions::default().max_decimals(2);
+        assert_eq!(to_decimal(&parsed("1.999"), &options), "2");
+        assert_eq!(
+            to_decimal(&parsed("1.999"), &options.clone().trailing_zeros(true)),
+            "2.00"
+        );
+        assert_eq!(
+            to_decimal(&parsed("1.999"), &options.clone().min_decimals(1)),
+            "2.0"
+        );
+        assert_eq!(
+            to_decimal(&parsed("-9.999"), &options.clone().fixed(2)),
+            "-10.00"
+        );
+        assert_eq!(
+            to_decimal(&parsed("1.205"), &options.clone().trailing_zeros(true)),
+            "1.21"
+        );
+        assert_eq!(
+            to_decimal(&parsed("1.201"), &options.clone().trailing_zeros(true)),
+            "1.20"
+        );
+        assert_eq!(
+            to_decimal(&parsed("1.5"), &options.trailing_zeros(true)),
+            "1.5"
+        );
+        assert_eq!(
+            to_decimal(
+                &parsed("9.996"),
+                &FormatOptions::default()
+                    .notation(Notation::Scientific)
+                    .fixed(2)
+            ),
+            "1.00e1"
+        );
+        let fixed = FormatOptions::default().fixed(2);
+        assert_eq!(format!("{}", parsed("12.5").formatted(fixed)), "12.50");
+        assert_eq!(format!("{:.2}", parsed("12.5")), "12.5");
+    }
+
+    #[test]
+    fn it_formats_significant_digits() {
+        let options = FormatOptions::default().significant_digits(4);
+        let check = |value: &str, expected: &str| {
+            assert_eq!(to_decimal(&parsed(value), &options), expected);
+        };
+        check("0.000123", "0.0001230");
+        check("-0.000123", "-0.0001230");
+        check("123000", "1.230e5");
+        check("123.456", "123.5");
+        check("1.23456", "1.235");
+        check("1234", "1234");
+        check("12345", "1.235e4");
+        check("0.00000123", "0.000001230");
+        check("0.000000123", "1.230e-7");
+        check("0.0000123", "0.00001230");
+        check("0", "0.000");
+        check("9.9996", "10.00");
+        check("9999.6", "1.000e4");
+        check("0.00099996", "0.001000");
+
+        assert_eq!(
+            to_decimal(
+                &parsed("123.456"),
+                &options.clone().rounding(Rounding::DOWN)
+            ),
+            "123.4"
+        );
+        assert_eq!(
+            to_decimal(
+                &parsed("0.5"),
+                &options.clone().notation(Notation::Scientific)
+            ),
+            "5.000e-1"
+        );
+        assert_eq!(
+            to_decimal(
+                &SafeDecimal::from(255.5),
+                &options.radix(Radix::Hexadecimal)
+            ),
+            "ff.80"
+        );
+        let two = FormatOptions::default().significant_digits(2);
+        assert_eq!(
+            to_decimal(
+                &SafeDecimal::from(255.5),
+                &two.clone().radix(Radix::Hexadecimal)
+            ),
+            "1.0e2"
+        );
+        assert_eq!(
+            to_decimal(&parsed("-0.0001"), &two.clone().rounding(Rounding::DOWN)),
+            "-0.00010"
+        );
+    }
+
+    #[test]
+    fn it_formats_significant_digits_in_engineering_notation() {
+        let options = FormatOptions::default()
+            .notation(Notation::Engineering)
+            .significant_digits(2);
+        assert_eq!(to_decimal(&parsed("123456"), &options), "120e3");
+        assert_eq!(to_decimal(&parsed("12345"), &options), "12e3");
+        assert_eq!(to_decimal(&parsed("1234"), &options), "1.2e3");
+        assert_eq!(to_decimal(&parsed("999999"), &options), "1.0e6");
+        assert_eq!(to_decimal(&parsed("0.000995"), &options), "1.0e-3");
+        assert_eq!(to_decimal(&parsed("0.0995"), &options), "100e-3");
+    }
+
+    #[test]
+    fn it_groups_digits() {
+        let value = "1234567.89".parse::<SafeDecimal<f64>>().unwrap();
+        let with = |separators: Separators| {
+            to_decimal(&value, &FormatOptions::default().separators(separators))
+        };
+        assert_eq!(with(Separators::default()), "1234567.89");
+        assert_eq!(with(Separators::default().group(',')), "1,234,567.89");
+        assert_eq!(
+            with(Separators::default().decimal(',').group('.')),
+            "1.234.567,89"
+        );
+        assert_eq!(
+            with(Separators::default().decimal(',').group(' ')),
+            "1 234 567,89"
+        );
+        assert_eq!(
+            with(Separators::default().group(',').group_sizes(3, 2)),
+            "12,34,567.89"
+        );
+        assert_eq!(with(Separators::default().decimal(',')), "1234567,89");
+
+        let options = FormatOptions::default().separators(Separators::default().group(','));
+        assert_eq!(to_decimal(&SafeDecimal::from(123.0), &options), "123");
+        assert_eq!(to_decimal(&SafeDecimal::from(1234.0), &options), "1,234");
+        assert_eq!(
+            to_decimal(&SafeDecimal::from(-123456.0), &options),
+            "-123,456"
+        );
+        assert_eq!(to_decimal(&SafeDecimal::from(0.5), &options), "0.5");
+        assert_eq!(
+            to_decimal(&SafeDecimal::from(-9999.999), &options.clone().fixed(2)),
+            "-10,000.00"
+        );
+        assert_eq!(
+            to_decimal(
+                &frac(1000.0, 3.0),
+                &options
+                    .clone()
+                    .max_decimals(5)
+                    .repetend(RepetendStyle::Parentheses)
+            ),
+            "333.(3)"
+        );
+        assert_eq!(
+            to_decimal(
+                &SafeDecimal::from(3735928559.0),
+                &options
+                    .radix(Radix::Hexadecimal)
+                    .prefix(true)
+                    .separators(Separators::default().group('_').group_sizes(4, 4))
+            ),
+            "0xdead_beef"
+        );
+        assert_eq!(
+            to_decimal(
+                &SafeDecimal::from(1.5),
+                &FormatOptions::default()
+                    .notation(Notation::Scientific)
+                    .separators(Separators::default().decimal(','))
+            ),
+            "1,5e0"
+        );
+    }
+
+    #[test]
+    fn it_honors_width_and_flags() {
+        let value = SafeDecimal::from(1.5);
+        assert_eq!(format!("{:8}|", value), "     1.5|");
+        assert_eq!(format!("{:<8}|", value), "1.5     |");
+        assert_eq!(format!("{:^9}|", value), "   1.5   |");
+        assert_eq!(format!("{:*^9}", value), "***1.5***");
+        assert_eq!(format!("{:+}", value), "+1.5");
+        assert_eq!(format!("{:08}", -value), "-00001.5");
+        assert_eq!(format!("{:+08.0}", value), "+0000002");
+        assert_eq!(format!("{:>10e}", SafeDecimal::from(1500.0)), "     1.5e3");
+        assert_eq!(format!("{:2}", SafeDecimal::from(1234.0)), "1234");
+    }
+
+    #[test]
+    fn it_implements_radix_formatting() {
+        assert_eq!(format!("{:x}", SafeDecimal::from(255.5)), "ff.8");
+        assert_eq!(format!("{:#x}", SafeDecimal::from(-255.5)), "-0xff.8");
+        assert_eq!(format!("{:b}", SafeDecimal::from(2.5)), "10.1");
+        assert_eq!(format!("{:#010b}", SafeDecimal::from(2.5)), "0b000010.1");
+        assert_eq!(format!("{:o}", SafeDecimal::from(8.125)), "10.1");
+        assert_eq!(format!("{:.1x}", frac(1.0, 3.0)), "0.5");
+        assert_eq!(format!("{:#o}", SafeDecimal::from(0.0)), "0o0");
+    }
+
+    #[test]
+    fn it_formats_with_custom_options() {
+        let value = SafeDecimal::from(1.99);
+        let floor = FormatOptions::default().rounding(Rounding::FLOOR);
+        assert_eq!(format!("{:.1}", value.formatted(floor.clone())), "1.9");
+        assert_eq!(format!("{:>6.1}", (-value).formatted(floor)), "    -2");
+        let options = FormatOptions::default()
+            .radix(Radix::Hexadecimal)
+            .prefix(true);
+        assert_eq!(
+            format!("{}", SafeDecimal::from(-255.5).formatted(options.clone())),
+            "-0xff.8"
+        );
+        assert_eq!(
+            format!(
+                "{:010}",
+                SafeDecimal::from(-255.5).formatted(options.clone())
+            ),
+            "-0x000ff.8"
+        );
+        assert_eq!(
+            format!("{:+}", SafeDecimal::from(255.5).formatted(options.clone())),
+            "+0xff.8"
+        );
+        assert_eq!(
+            format!(
+                "{:*<9}|",
+                SafeDecimal::from(-255.5).formatted(options.clone())
+            ),
+            "-0xff.8**|"
+        );
+        assert_eq!(
+            format!("{:^9}|", SafeDecimal::from(255.5).formatted(options)),
+            " 0xff.8  |"
+        );
+    }
+
+    #[test]
+    fn it_implements_exp_formatting() {
+        let value = SafeDecimal::from(1234.5);
+        assert_eq!(format!("{:e}", value), "1.2345e3");
+        assert_eq!(format!("{:E}", value), "1.2345E3");
+        assert_eq!(format!("{:.2e}", value), "1.23e3");
+        assert_eq!(format!("{:.0e}", SafeDecimal::from(2.5)), "2e0");
+        assert_eq!(format!("{:.0e}", SafeDecimal::from(3.5)), "4e0");
+        assert_eq!(format!("{:e}", -frac(1.0, 8.0)), "-1.25e-1");
+    }
+
+    #[test]
+    fn it_formats_any_radix() {
+        let options = |radix| FormatOptions::default().radix(Radix::Custom(radix));
+        assert_eq!(to_decimal(&frac(1.0, 3.0), &options(3)), "0.1");
+        assert_eq!(to_decimal(&frac(-52.0, 9.0), &options(3)), "-12.21");
+        assert_eq!(to_decimal(&frac(12.5, 1.0), &options(12)), "10.6");
+        assert_eq!(to_decimal(&frac(1295.5, 1.0), &options(36)), "zz.i");
+        assert_eq!(to_decimal(&frac(0.0, 1.0), &options(7)), "0");
+        assert_eq!(
+            to_decimal(
+                &frac(1.0, 7.0),
+                &options(12).repetend(RepetendStyle::Parentheses)
+            ),
+            "0.(186a35)"
+        );
+        // 1/2 = 0.111… in base 3, so it's exactly halfway after any number of digits
+        assert_eq!(
+            to_decimal(&frac(1.0, 2.0), &options(3).max_decimals(2)),
+            "0.12"
+        );
+        assert_eq!(
+            to_decimal(
+                &frac(1.0, 2.0),
+                &options(3).max_decimals(2).rounding(Rounding::HALF_DOWN)
+            ),
+            "0.11"
+        );
+
+        for (value, radix) in [
+            (frac(-52.0, 9.0), 3),
+            (frac(1.0, 144.0), 12),
+            (frac(1295.5, 1.0), 36),
+        ] {
+            let formatted = to_decimal(&value, &options(radix));
+            assert_eq!(
+                SafeDecimal::<f64>::from_str_radix(&formatted, radix).unwrap(),
+                value
+            );
+        }
+    }
+
+    #[test]
+    fn it_rejects_invalid_options_when_formatting() {
+        use std::fmt::Write;
+
+        let value = frac(1.0, 3.0);
+        let invalid = [
+            (
+                FormatOptions::default().radix(Radix::Custom(37)),
+                FormatOptionsError::UnsupportedRadix(37),
+            ),
+            (
+                FormatOptions::default().radix(Radix::Custom(1)),
+                FormatOptionsError::UnsupportedRadix(1),
+            ),
+            (
+                FormatOptions::default().separators(Separators::default().group_sizes(0, 3)),
+                FormatOptionsError::ZeroGroupSize,
+            ),
+            (
+                FormatOptions::default().separators(Separators::default().group_sizes(3, 0)),
+                FormatOptionsError::ZeroGroupSize,
+            ),
+            (
+                FormatOptions::default().separators(Separators::default().group('.')),
+                FormatOptionsError::GroupIsDecimal,
+            ),
+            (
+                FormatOptions::default().separators(Separators::default().decimal(',').group(',')),
+                FormatOptionsError::GroupIsDecimal,
+            ),
+            (
+                FormatOptions::default().significant_digits(0),
+                FormatOptionsError::ZeroSignificantDigits,
+            ),
+        ];
+        for (options, error) in invalid {
+            assert_eq!(options.validate(), Err(error));
+            let mut output = String::new();
+            assert!(write!(output, "{}", value.formatted(options)).is_err());
+        }
+        assert_eq!(FormatOptions::default().validate(), Ok(()));
+        let european = Separators::default().decimal(',').group('.');
+        assert_eq!(
+            FormatOptions::default().separators(european).validate(),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn it_formats_repetends() {
+        let options = FormatOptions::default().repetend(RepetendStyle::Parentheses);
+        assert_eq!(to_decimal(&frac(1.0, 7.0), &options), "0.(142857)");
+        assert_eq!(to_decimal(&frac(1.0, 6.0), &options), "0.1(6)");
+        assert_eq!(to_decimal(&frac(-1.0, 3.0), &options), "-0.(3)");
+        assert_eq!(to_decimal(&frac(22.0, 7.0), &options), "3.(142857)");
+        assert_eq!(to_decimal(&frac(17.0, 12.0), &options), "1.41(6)");
+        assert_eq!(to_decimal(&frac(1.0, 4.0), &options), "0.25");
+        assert_eq!(to_decimal(&frac(5.0, 1.0), &options), "5");
+
+        let options = FormatOptions::default().repetend(RepetendStyle::Overline);
+        assert_eq!(to_decimal(&frac(1.0, 6.0), &options), "0.16\u{305}");
+        assert_eq!(to_decimal(&frac(1.0, 11.0), &options), "0.0\u{305}9\u{305}");
+
+        let options = FormatOptions::default().repetend(RepetendStyle::Ellipsis);
+        assert_eq!(to_decimal(&frac(1.0, 3.0), &options), "0.333…");
+        assert_eq!(to_decimal(&frac(1.0, 6.0), &options), "0.1666…");
+        assert_eq!(to_decimal(&frac(1.0, 7.0), &options), "0.142857142857…");
+        assert_eq!(to_decimal(&frac(11.0, 999.0), &options), "0.011011…");
+
+        // The parser reads them back, even when the repetend ends with repeated digits
+        for (numerator, denominator) in [(11.0, 999.0), (1.0, 6.0), (1.0, 7.0), (34.0, 9990.0)] {
+            let value = frac(numerator, denominator);
+            let formatted = to_decimal(&value, &options);
+            assert_eq!(
+                formatted.parse::<SafeDecimal<f64>>(),
+                Ok(value),
+                "{formatted}"
+            );
+        }
+    }
+
+    #[test]
+    fn it_rounds_repetends_that_dont_fit() {
+        let options = FormatOptions::default()
+            .repetend(RepetendStyle::Parentheses)
+            .max_period(16);
+        assert_eq!(to_decimal(&frac(1.0, 97.0), &options), "0.0103092783505155");
+        assert_eq!(
+            to_decimal(&frac(1.0, 17.0), &options),
+            "0.(0588235294117647)"
+        );
+
+        let options = options.max_period(15);
+        assert_eq!(to_decimal(&frac(1.0, 17.0), &options), "0.0588235294117647");
+
+        let options = options.max_decimals(2);
+        assert_eq!(to_decimal(&frac(1.0, 6.0), &options), "0.1(6)");
+        assert_eq!(to_decimal(&frac(1.0, 3000.0), &options), "0");
     }
 }
@@ -1,37 +1,68 @@
-use std::{fmt::LowerExp, num::ParseIntError, str::FromStr};
+use std::{fmt::LowerExp, str::FromStr};
 
+pub use context::{Context, Signal, Signals};
 use convert::from_f64;
+pub use exact::Inexact;
 use format::to_decimal;
-pub use format::FormatOptions;
+pub use format::{
+    ExponentStyle, FormatOptions, FormatOptionsError, Formatted, Notation, Radix, RepetendStyle,
+    Rounding, RoundingDirection, Separators,
+};
 use num_traits::Float;
+pub use parsing::ParseDecimalError;
 
+mod big_uint;
+mod checked;
+mod context;
 mod convert;
 mod double;
+mod exact;
 mod format;
 mod iter_pad;
 mod ops;
 mod ord;
 mod parsing;
+mod round;
 
 #[derive(Debug, Clone, Copy)]
 pub struct SafeDecimal<T> {
-    pub numerator: T,
-    pub denominator: T,
+    numerator: T,
+    denominator: T,
 }
 
-impl<T: Float + std::fmt::Debug> FromStr for SafeDecimal<T> {
-    type Err = ParseIntError;
+/// Error returned by [`SafeDecimal::new`] when the components don't make a valid fraction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FractionError {
+    /// The numerator or the denominator is NaN or infinite.
+    NotFinite,
+    /// The denominator is zero.
+    ZeroDenominator,
+    /// The numerator or the denominator is subnormal, which loses the precision the operations rely on.
+    Subnormal,
+}
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s.split_once(".") {
-            Some((integer_part, fractional_part)) => {
-                parsing::from_parts(integer_part, fractional_part)
+impl std::fmt::Display for FractionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FractionError::NotFinite => write!(f, "numerator and denominator must be finite"),
+            FractionError::ZeroDenominator => write!(f, "denominator can't be zero"),
+            FractionError::Subnormal => {
+                write!(f, "numerator and denominator can't be subnormal")
             }
-            None => parsing::from_integer(s),
         }
     }
 }
 
+impl std::error::Error for FractionError {}
+
+impl<T: Float + std::fmt::Debug> FromStr for SafeDecimal<T> {
+    type Err = ParseDecimalError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parsing::parse(s, false)
+    }
+}
+
 impl<T: Float + LowerExp + std::fmt::Debug> From<T> for SafeDecimal<T> {
     fn from(value: T) -> Self {
         from_f64(value)
@@ -39,15 +70,94 @@ impl<T: Float + LowerExp + std::fmt::Debug> From<T> for SafeDecimal<T> {
 }
 
 impl<T: Float> SafeDecimal<T> {
+    /// Builds the fraction `numerator / denominator`, in its normalized form.
+    pub fn new(numerator: T, denominator: T) -> Result<Self, FractionError> {
+        if !numerator.is_finite() || !denominator.is_finite() {
+            return Err(FractionError::NotFinite);
+        }
+        if denominator == T::zero() {
+            return Err(FractionError::ZeroDenominator);
+        }
+        if (numerator != T::zero() && !numerator.is_normal()) || !denominator.is_normal() {
+            return Err(FractionError::Subnormal);
+        }
+
+        Ok(SafeDecimal {
+            numerator,
+            denominator,
+        }
+        .normalize())
+    }
+
+    /// Same as `from_str`, but fails with `ParseDecimalError::PrecisionLost` instead of rounding
+    /// digits that can't be represented.
+    pub fn from_str_exact(s: &str) -> Result<Self, ParseDecimalError>
+    where
+        T: std::fmt::Debug,
+    {
+        parsing::parse(s, true)
+    }
+
+    /// Parses a number written in any radix from 2 to 36, such as `-z.i` in base 36. It takes an
+    /// optional sign, but no `0x`-style prefix or exponent.
+    ///
+    /// Fractional digits are exact as long as the odd factors of `radix^digits` fit in the mantissa,
+    /// and get rounded otherwise.
+    pub fn from_str_radix(s: &str, radix: u32) -> Result<Self, ParseDecimalError>
+    where
+        T: std::fmt::Debug,
+    {
+        parsing::parse_radix(s, radix, false)
+    }
+
+    /// Parses a number written with the given separators, such as `1.234.567,89`.
+    pub fn from_str_with_separators(
+        s: &str,
+        separators: &Separators,
+    ) -> Result<Self, ParseDecimalError>
+    where
+        T: std::fmt::Debug,
+    {
+        parsing::parse_separated(s, separators, false)
+    }
+
+    pub fn numerator(&self) -> T {
+        self.numerator
+    }
+
+    pub fn denominator(&self) -> T {
+        self.denominator
+    }
+
     pub fn to_float(&self) -> T {
         self.numerator / self.denominator
     }
 
-    pub fn to_decimal(&self, options: FormatOptions) -> String {
+    /// # Panics
+    ///
+    /// If the options are invalid, see [`FormatOptions::validate`]. Formatting through
+    /// [`SafeDecimal::formatted`] returns an error instead.
+    ///
+    /// Values that overflowed are written as the largest finite value, like a [`Context`] saturates
+    /// them, and undefined ones like `0/0` as `NaN`.
+    pub fn to_decimal(&self, options: FormatOptions) -> String
+    where
+        T: std::fmt::Debug,
+    {
+        if let Err(err) = options.validate() {
+            panic!("{}", err);
+        }
         to_decimal(self, &options)
     }
 
-    pub fn to_string(&self) -> String {
-        self.to_decimal(FormatOptions::default())
+    /// Formats with the given options through `format!`, `write!` and the like.
+    pub fn formatted(&self, options: FormatOptions) -> Formatted<'_, T> {
+        Formatted::new(self, options)
     }
 }
+
+/// Shorthand for `SafeDecimal::from`, shared by the unit tests.
+#[cfg(test)]
+fn d(value: f64) -> SafeDecimal<f64> {
+    SafeDecimal::from(value)
+}
@@ -3,8 +3,10 @@ use std::fmt::LowerExp;
 use num_traits::Float;
 
 use crate::{
-    double::{construct_float, exponential_form, from_exponential_from, parse_float},
-    SafeDecimal,
+    big_uint::BigUint,
+    double::{construct_float, exponential_form, from_exponential_from, is_f64, parse_float},
+    format::{from_integers, to_integers},
+    Inexact, SafeDecimal,
 };
 
 impl<T: Float> SafeDecimal<T> {
@@ -33,6 +35,76 @@ impl<T: Float> SafeDecimal<T> {
             self
         }
     }
+
+    /// Canonical representation of the value: the fraction is fully reduced, the sign is on the
+    /// numerator, zero is always `0/1`, and the factors of 2 are balanced between the numerator
+    /// and the denominator.
+    ///
+    /// Two values that are equal have the same canonical representation.
+    pub fn normalize(self) -> SafeDecimal<T> {
+        if !self.numerator.is_finite() || !self.denominator.is_finite() {
+            return self;
+        }
+        if self.numerator == T::zero() {
+            return SafeDecimal {
+                numerator: T::zero(),
+                denominator: T::one(),
+            };
+        }
+
+        let (n_sign, n_int, n_exp) = exponential_form(self.numerator);
+        let (d_sign, d_int, d_exp) = exponential_form(self.denominator);
+
+        // Both integers are odd, so after removing their gcd the fraction can't be reduced further.
+        let gcd_int = gcd(n_int, d_int);
+
+        balanced(
+            n_sign ^ d_sign,
+            (n_int / gcd_int, n_exp),
+            (d_int / gcd_int, d_exp),
+        )
+    }
+}
+
+/// `(n_int * 2^n_exp) / (d_int * 2^d_exp)` with the factors of 2 balanced between the numerator
+/// and the denominator. Exponents too far apart for that give an infinite numerator or zero.
+pub fn balanced<T: Float>(
+    sign: u8,
+    (n_int, n_exp): (u64, i32),
+    (d_int, d_exp): (u64, i32),
+) -> SafeDecimal<T> {
+    // Only the difference of the float exponents matters. Split it evenly, giving the numerator
+    // the extra 2 if it's odd, so that every representation of the same value ends up in the
+    // same place and both components stay in range.
+    let n_top = 63 - n_int.leading_zeros() as i32;
+    let d_top = 63 - d_int.leading_zeros() as i32;
+    let exp_diff = (n_exp + n_top) - (d_exp + d_top);
+    let n_float_exp = exp_diff - exp_diff.div_euclid(2);
+    let d_float_exp = n_float_exp - exp_diff;
+
+    let (min_exp, max_exp) = if is_f64::<T>() {
+        (-1022, 1023)
+    } else {
+        (-126, 127)
+    };
+    if n_float_exp > max_exp || d_float_exp < min_exp {
+        let infinity = T::infinity();
+        return SafeDecimal {
+            numerator: if sign == 1 { -infinity } else { infinity },
+            denominator: T::one(),
+        };
+    }
+    if n_float_exp < min_exp || d_float_exp > max_exp {
+        return SafeDecimal {
+            numerator: T::zero(),
+            denominator: T::one(),
+        };
+    }
+
+    reduce_exponent(SafeDecimal {
+        numerator: from_exponential_from(sign, n_int, n_float_exp - n_top),
+        denominator: from_exponential_from(0, d_int, d_float_exp - d_top),
+    })
 }
 
 impl<T: Float + std::fmt::Debug> std::ops::Add<SafeDecimal<T>> for SafeDecimal<T> {
@@ -57,32 +129,24 @@ impl<T: Float + std::fmt::Debug> std::ops::Add<SafeDecimal<T>> for SafeDecimal<T
     }
 }
 
-impl<T: Float + LowerExp + std::fmt::Debug> std::ops::Add<T> for SafeDecimal<T> {
-    type Output = Self;
-
-    fn add(self, rhs: T) -> Self::Output {
-        self + SafeDecimal::from(rhs)
-    }
-}
-
 impl<T: Float> std::ops::Neg for SafeDecimal<T> {
     type Output = Self;
 
     fn neg(self) -> Self::Output {
-        return SafeDecimal {
+        SafeDecimal {
             numerator: -self.numerator,
             denominator: self.denominator,
-        };
+        }
     }
 }
 
-// impl<T: Float> std::ops::Sub for SafeDecimal<T> {
-//     type Output = Self;
+impl<T: Float + std::fmt::Debug> std::ops::Sub for SafeDecimal<T> {
+    type Output = Self;
 
-//     fn sub(self, rhs: Self) -> Self::Output {
-//         self + -rhs
-//     }
-// }
+    fn sub(self, rhs: Self) -> Self::Output {
+        self + -rhs
+    }
+}
 
 impl<T: Float> std::ops::Mul for SafeDecimal<T> {
     type Output = Self;
@@ -106,12 +170,142 @@ impl<T: Float> std::ops::Mul for SafeDecimal<T> {
 impl<T: Float> std::ops::Div for SafeDecimal<T> {
     type Output = Self;
 
+    #[allow(clippy::suspicious_arithmetic_impl)]
     fn div(self, rhs: Self) -> Self::Output {
         self * rhs.inv().expect("Division by zero")
     }
 }
 
-fn simplify_factors<T: Float>(a: T, b: T) -> (T, T) {
+impl<T: Float + std::fmt::Debug> std::ops::Rem for SafeDecimal<T> {
+    type Output = Self;
+
+    fn rem(self, rhs: Self) -> Self::Output {
+        // Same semantics as the primitive types: the quotient is truncated towards zero,
+        // so the result has the sign of the dividend.
+        truncated_remainder(self, rhs).unwrap_or_else(Inexact::value)
+    }
+}
+
+// The operators above are implemented on owned values. The rest of the family (borrowed operands,
+// scalar operands and the assignment forms) just forwards to them.
+macro_rules! forward_binop {
+    (impl $imp:ident, $method:ident where T: $($bound:tt)+) => {
+        impl<'a, T: $($bound)+> std::ops::$imp<&'a SafeDecimal<T>> for SafeDecimal<T> {
+            type Output = SafeDecimal<T>;
+
+            fn $method(self, rhs: &'a SafeDecimal<T>) -> Self::Output {
+                std::ops::$imp::$method(self, *rhs)
+            }
+        }
+
+        impl<'a, T: $($bound)+> std::ops::$imp<SafeDecimal<T>> for &'a SafeDecimal<T> {
+            type Output = SafeDecimal<T>;
+
+            fn $method(self, rhs: SafeDecimal<T>) -> Self::Output {
+                std::ops::$imp::$method(*self, rhs)
+            }
+        }
+
+        impl<'a, 'b, T: $($bound)+> std::ops::$imp<&'b SafeDecimal<T>> for &'a SafeDecimal<T> {
+            type Output = SafeDecimal<T>;
+
+            fn $method(self, rhs: &'b SafeDecimal<T>) -> Self::Output {
+                std::ops::$imp::$method(*self, *rhs)
+            }
+        }
+
+        impl<T: Float + LowerExp + std::fmt::Debug> std::ops::$imp<T> for SafeDecimal<T> {
+            type Output = SafeDecimal<T>;
+
+            fn $method(self, rhs: T) -> Self::Output {
+                std::ops::$imp::$method(self, SafeDecimal::from(rhs))
+            }
+        }
+
+        // Can't be generic over T, because of the orphan rules.
+        forward_binop!(impl $imp, $method for f64);
+        forward_binop!(impl $imp, $method for f32);
+    };
+    (impl $imp:ident, $method:ident for $float:ty) => {
+        impl std::ops::$imp<SafeDecimal<$float>> for $float {
+            type Output = SafeDecimal<$float>;
+
+            fn $method(self, rhs: SafeDecimal<$float>) -> Self::Output {
+                std::ops::$imp::$method(SafeDecimal::from(self), rhs)
+            }
+        }
+    };
+}
+
+forward_binop!(impl Add, add where T: Float + std::fmt::Debug);
+forward_binop!(impl Sub, sub where T: Float + std::fmt::Debug);
+forward_binop!(impl Mul, mul where T: Float);
+forward_binop!(impl Div, div where T: Float);
+forward_binop!(impl Rem, rem where T: Float + std::fmt::Debug);
+
+macro_rules! forward_assign_op {
+    (impl $imp:ident, $method:ident, $op_imp:ident, $op_method:ident where T: $($bound:tt)+) => {
+        impl<T: $($bound)+> std::ops::$imp for SafeDecimal<T> {
+            fn $method(&mut self, rhs: SafeDecimal<T>) {
+                *self = std::ops::$op_imp::$op_method(*self, rhs);
+            }
+        }
+
+        impl<'a, T: $($bound)+> std::ops::$imp<&'a SafeDecimal<T>> for SafeDecimal<T> {
+            fn $method(&mut self, rhs: &'a SafeDecimal<T>) {
+                *self = std::ops::$op_imp::$op_method(*self, *rhs);
+            }
+        }
+
+        impl<T: Float + LowerExp + std::fmt::Debug> std::ops::$imp<T> for SafeDecimal<T> {
+            fn $method(&mut self, rhs: T) {
+                *self = std::ops::$op_imp::$op_method(*self, SafeDecimal::from(rhs));
+            }
+        }
+    };
+}
+
+forward_assign_op!(impl AddAssign, add_assign, Add, add where T: Float + std::fmt::Debug);
+forward_assign_op!(impl SubAssign, sub_assign, Sub, sub where T: Float + std::fmt::Debug);
+forward_assign_op!(impl MulAssign, mul_assign, Mul, mul where T: Float);
+forward_assign_op!(impl DivAssign, div_assign, Div, div where T: Float);
+forward_assign_op!(impl RemAssign, rem_assign, Rem, rem where T: Float + std::fmt::Debug);
+
+/// Remainder of `a / b` with the quotient truncated towards zero, computed with integers so that
+/// the quotient can be of any size.
+pub fn truncated_remainder<T: Float>(
+    a: SafeDecimal<T>,
+    b: SafeDecimal<T>,
+) -> Result<SafeDecimal<T>, Inexact<T>> {
+    assert!(b.numerator != T::zero(), "Division by zero");
+    let (a_num, a_den) = to_integers(a.numerator, a.denominator);
+    let (b_num, b_den) = to_integers(b.numerator, b.denominator);
+
+    // |a| - q |b| = (a_num * b_den - q * b_num * a_den) / (a_den * b_den)
+    let (_, mut remainder) = a_num.mul(&b_den).div_rem(&b_num.mul(&a_den));
+
+    // Both denominators are an odd integer that fits in the mantissa times a power of 2. Taking out
+    // the odd factors the remainder shares with them leaves a fraction that fits whenever the result
+    // can be represented.
+    let mut denominator = BigUint::from(1_u64);
+    for side in [a_den, b_den] {
+        let (mantissa, exponent, _) = side.to_mantissa(64);
+        let twos = mantissa.trailing_zeros();
+        let odd = mantissa >> twos;
+        let common = gcd(remainder.clone().div_rem_small(odd), odd);
+        remainder.div_rem_small(common);
+        denominator = denominator.mul(&BigUint::from_shifted(odd / common, exponent as u32 + twos));
+    }
+
+    let is_negative = (a.numerator < T::zero()) != (a.denominator < T::zero());
+    match from_integers::<T>(&remainder, &denominator) {
+        Ok(result) if is_negative => Ok(-result),
+        Err(Inexact(result)) if is_negative => Err(Inexact(-result)),
+        result => result,
+    }
+}
+
+pub fn simplify_factors<T: Float>(a: T, b: T) -> (T, T) {
     if a == T::zero() {
         return (T::zero(), T::one());
     }
@@ -135,13 +329,13 @@ fn simplify_factors<T: Float>(a: T, b: T) -> (T, T) {
     let a_exp_simplified = a_exp - exp_change;
     let b_exp_simplified = b_exp - exp_change;
 
-    return (
+    (
         from_exponential_from(a_sign, a_int_simplified, a_exp_simplified),
         from_exponential_from(b_sign, b_int_simplified, b_exp_simplified),
-    );
+    )
 }
 
-fn reduce_exponent<T: Float>(value: SafeDecimal<T>) -> SafeDecimal<T> {
+pub fn reduce_exponent<T: Float>(value: SafeDecimal<T>) -> SafeDecimal<T> {
     if value.numerator == num_traits::zero() {
         return SafeDecimal {
             numerator: num_traits::zero(),
@@ -155,10 +349,10 @@ fn reduce_exponent<T: Float>(value: SafeDecimal<T>) -> SafeDecimal<T> {
     let exp_sum = n_exp + d_exp;
     let exp_change = exp_sum / 2;
 
-    return SafeDecimal {
+    SafeDecimal {
         numerator: construct_float::<T>(n_sign, n_exp - exp_change, n_mant),
         denominator: construct_float::<T>(d_sign, d_exp - exp_change, d_mant),
-    };
+    }
 }
 
 fn gcd(a: u64, b: u64) -> u64 {
@@ -177,10 +371,181 @@ fn gcd(a: u64, b: u64) -> u64 {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::d;
+    use crate::FractionError;
 
     #[test]
     fn it_works() {
         println!("{:?}", simplify_factors(90.0, 420.0));
         println!("{:?}", exponential_form(0.2_f32));
     }
+
+    #[test]
+    fn it_normalizes_values() {
+        fn check(value: SafeDecimal<f64>, numerator: f64, denominator: f64) {
+            let normalized = value.normalize();
+            assert_eq!(normalized.numerator.to_bits(), numerator.to_bits());
+            assert_eq!(normalized.denominator.to_bits(), denominator.to_bits());
+        }
+        let frac = |numerator, denominator| SafeDecimal {
+            numerator,
+            denominator,
+        };
+
+        check(frac(-0.0, 3.0), 0.0, 1.0);
+        check(frac(6.0, 9.0), 1.0, 1.5);
+        check(frac(1.0, -3.0), -1.0, 3.0);
+        check(frac(-4.0, -8.0), 1.0, 2.0);
+        // Components far apart get balanced instead of overflowing.
+        check(frac(1.0, 2f64.powi(-1000)), 2f64.powi(500), 2f64.powi(-500));
+        check(frac(2f64.powi(-1000), 1.0), 2f64.powi(-500), 2f64.powi(500));
+
+        let expected = frac(3.0, 7.0).normalize();
+        for value in [
+            frac(6.0, 14.0),
+            frac(0.75, 1.75),
+            frac(30.0, 70.0),
+            frac(-3.0, -7.0),
+        ] {
+            check(value, expected.numerator, expected.denominator);
+        }
+        let expected = d(0.3).normalize();
+        check(d(0.1) + d(0.2), expected.numerator, expected.denominator);
+        check(d(0.6) / d(2.0), expected.numerator, expected.denominator);
+    }
+
+    #[test]
+    fn it_validates_new_fractions() {
+        let third = SafeDecimal::new(2.0, -6.0).unwrap();
+        assert_eq!(third, -(d(3.0).inv().unwrap()));
+        assert!(third.numerator() < 0.0);
+        assert!(third.denominator() > 0.0);
+
+        let zero = SafeDecimal::new(-0.0_f64, 5.0).unwrap();
+        assert_eq!(zero.numerator().to_bits(), 0.0_f64.to_bits());
+        assert_eq!(zero.denominator(), 1.0);
+
+        assert_eq!(
+            SafeDecimal::new(1.0, 0.0),
+            Err(FractionError::ZeroDenominator)
+        );
+        assert_eq!(
+            SafeDecimal::new(1.0, -0.0),
+            Err(FractionError::ZeroDenominator)
+        );
+        assert_eq!(
+            SafeDecimal::new(f64::NAN, 1.0),
+            Err(FractionError::NotFinite)
+        );
+        assert_eq!(
+            SafeDecimal::new(1.0, f64::INFINITY),
+            Err(FractionError::NotFinite)
+        );
+        assert_eq!(SafeDecimal::new(1e-310, 1.0), Err(FractionError::Subnormal));
+    }
+
+    #[test]
+    fn it_subtracts() {
+        assert_eq!(d(0.3) - d(0.1), d(0.2));
+        assert_eq!(d(0.1) - d(0.3), d(-0.2));
+        assert_eq!(d(1.0) - d(3.0).inv().unwrap(), d(2.0) / d(3.0));
+    }
+
+    #[test]
+    fn it_calculates_the_truncated_remainder() {
+        assert_eq!(d(7.5) % d(2.0), d(1.5));
+        assert_eq!(d(-7.5) % d(2.0), d(-1.5));
+        assert_eq!(d(7.5) % d(-2.0), d(1.5));
+        assert_eq!(d(1.0) % d(0.3), d(0.1));
+        assert_eq!(d(1.0) % (d(1.0) / d(3.0)), d(0.0));
+        assert_eq!(d(2.0) % (d(2.0) / d(3.0)), d(0.0));
+        assert_eq!(d(0.2) % d(0.7), d(0.2));
+        assert_eq!(d(1.0) % d(0.1), d(0.0));
+    }
+
+    #[test]
+    fn it_calculates_remainders_of_large_quotients() {
+        // The quotients are above 2^53, where floats can't tell the integers apart.
+        assert_eq!(d(1e17) % d(3.0), d(1.0));
+        assert_eq!(d(-1e17) % d(3.0), d(-1.0));
+        // The float closest to 1e300 is 1 modulo 7.
+        assert_eq!(SafeDecimal::new(1e300, 1.0).unwrap() % d(7.0), d(1.0));
+        assert_eq!(d(1e17) % (d(1.0) / d(3.0)), d(0.0));
+        assert_eq!(d(1e17) % (d(3.0) / d(7.0)), d(1.0) / d(7.0));
+    }
+
+    #[test]
+    #[should_panic(expected = "Division by zero")]
+    fn it_panics_on_remainder_by_zero() {
+        let _ = d(1.0) % d(0.0);
+    }
+
+    #[test]
+    fn it_assigns_in_place() {
+        let mut value = d(0.1);
+        value += d(0.2);
+        assert_eq!(value, d(0.3));
+        value -= d(0.5);
+        assert_eq!(value, d(-0.2));
+        value *= d(3.0);
+        assert_eq!(value, d(-0.6));
+        value /= d(0.4);
+        assert_eq!(value, d(-1.5));
+        value %= d(0.4);
+        assert_eq!(value, d(-0.3));
+    }
+
+    #[test]
+    fn it_operates_with_scalars() {
+        assert_eq!(d(0.1) + 0.2, d(0.3));
+        assert_eq!(d(0.3) - 0.1, d(0.2));
+        assert_eq!(d(0.1) * 3.0, d(0.3));
+        assert_eq!(d(0.3) / 3.0, d(0.1));
+        assert_eq!(d(0.7) % 0.2, d(0.1));
+
+        assert_eq!(0.2 + d(0.1), d(0.3));
+        assert_eq!(0.3 - d(0.1), d(0.2));
+        assert_eq!(3.0 * d(0.1), d(0.3));
+        assert_eq!(1.0 / d(3.0), d(3.0).inv().unwrap());
+        assert_eq!(0.7 % d(0.2), d(0.1));
+        assert_eq!(
+            0.5_f32 * SafeDecimal::from(0.2_f32),
+            SafeDecimal::from(0.1_f32)
+        );
+
+        let mut value = d(0.1);
+        value += 0.2;
+        value *= 2.0;
+        value -= 0.1;
+        value /= 0.5;
+        assert_eq!(value, d(1.0));
+    }
+
+    fn sum<T>(values: &[T]) -> T
+    where
+        T: Copy,
+        for<'a> &'a T: std::ops::Add<&'a T, Output = T>,
+    {
+        values[1..].iter().fold(values[0], |acc, v| &acc + v)
+    }
+
+    #[test]
+    #[allow(clippy::op_ref)]
+    fn it_operates_with_references() {
+        let (a, b) = (d(0.1), d(0.2));
+        assert_eq!(&a + &b, d(0.3));
+        assert_eq!(a + &b, d(0.3));
+        assert_eq!(&a + b, d(0.3));
+        assert_eq!(&b - &a, d(0.1));
+        assert_eq!(&a * &b, d(0.02));
+        assert_eq!(&a / &b, d(0.5));
+        assert_eq!(&b % &a, d(0.0));
+
+        let mut value = a;
+        value += &b;
+        value *= &b;
+        assert_eq!(value, d(0.06));
+
+        assert_eq!(sum(&[d(0.1), d(0.2), d(0.3)]), d(0.6));
+    }
 }
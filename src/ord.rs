@@ -1,15 +1,38 @@
-use std::cmp::Ordering;
+use std::{
+    cmp::Ordering,
+    hash::{Hash, Hasher},
+};
 
 use num_traits::Float;
 
-use crate::SafeDecimal;
+use crate::{double::exponential_form, SafeDecimal};
 
 impl<T: Float> Ord for SafeDecimal<T> {
     fn cmp(&self, other: &Self) -> Ordering {
-        s_cmp(
-            self.numerator * other.denominator,
-            self.denominator * other.numerator,
-        )
+        let components = [
+            self.numerator,
+            self.denominator,
+            other.numerator,
+            other.denominator,
+        ];
+        if components.iter().any(|v| !v.is_finite()) {
+            return s_cmp(
+                self.numerator * other.denominator,
+                self.denominator * other.numerator,
+            );
+        }
+
+        // a/b <=> c/d is the same as a*d <=> b*c as long as b*d is positive.
+        // The products can overflow or get rounded, so they get compared on their exact form instead.
+        let ordering = cmp_products(
+            (self.numerator, other.denominator),
+            (self.denominator, other.numerator),
+        );
+        if self.denominator.is_sign_negative() != other.denominator.is_sign_negative() {
+            ordering.reverse()
+        } else {
+            ordering
+        }
     }
 }
 impl<T: Float> PartialOrd for SafeDecimal<T> {
@@ -25,6 +48,15 @@ impl<T: Float> PartialEq for SafeDecimal<T> {
 }
 impl<T: Float> Eq for SafeDecimal<T> {}
 
+impl<T: Float> Hash for SafeDecimal<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // Equal values can have different numerators and denominators, but they share the same normal form.
+        let normalized = self.normalize();
+        normalized.numerator.integer_decode().hash(state);
+        normalized.denominator.integer_decode().hash(state);
+    }
+}
+
 fn s_cmp<T: Float>(a: T, b: T) -> std::cmp::Ordering {
     if a == b {
         Ordering::Equal
@@ -34,3 +66,145 @@ fn s_cmp<T: Float>(a: T, b: T) -> std::cmp::Ordering {
         Ordering::Less
     }
 }
+
+/// Compares `a.0 * a.1` against `b.0 * b.1` without rounding. All values must be finite.
+fn cmp_products<T: Float>(a: (T, T), b: (T, T)) -> Ordering {
+    let (a_negative, a_int, a_exp) = exact_product(a.0, a.1);
+    let (b_negative, b_int, b_exp) = exact_product(b.0, b.1);
+
+    match (a_int == 0, b_int == 0) {
+        (true, true) => return Ordering::Equal,
+        (true, false) => {
+            return if b_negative {
+                Ordering::Greater
+            } else {
+                Ordering::Less
+            }
+        }
+        (false, true) => {
+            return if a_negative {
+                Ordering::Less
+            } else {
+                Ordering::Greater
+            }
+        }
+        _ => {}
+    }
+    if a_negative != b_negative {
+        return if a_negative {
+            Ordering::Less
+        } else {
+            Ordering::Greater
+        };
+    }
+
+    let magnitude = cmp_magnitudes((a_int, a_exp), (b_int, b_exp));
+    if a_negative {
+        magnitude.reverse()
+    } else {
+        magnitude
+    }
+}
+
+/// `a * b` represented as `sign {int} * 2 ^ {exp}`. The integer part of each factor fits in 53
+/// bits, so the product always fits in a u128.
+fn exact_product<T: Float>(a: T, b: T) -> (bool, u128, i32) {
+    let (a_sign, a_int, a_exp) = exponential_form(a);
+    let (b_sign, b_int, b_exp) = exponential_form(b);
+    (
+        a_sign != b_sign,
+        a_int as u128 * b_int as u128,
+        a_exp + b_exp,
+    )
+}
+
+/// Compares `a.0 * 2 ^ a.1` against `b.0 * 2 ^ b.1`. Both integers must be non-zero.
+fn cmp_magnitudes(a: (u128, i32), b: (u128, i32)) -> Ordering {
+    // Position of the most significant bit decides, unless they're the same.
+    let a_top = a.1 + (128 - a.0.leading_zeros() as i32);
+    let b_top = b.1 + (128 - b.0.leading_zeros() as i32);
+    if a_top != b_top {
+        return a_top.cmp(&b_top);
+    }
+
+    // Same top bit: align the one with the bigger exponent to the other one. This can't overflow,
+    // because the one with the bigger exponent is the one with the fewer significant bits.
+    if a.1 >= b.1 {
+        (a.0 << (a.1 - b.1)).cmp(&b.0)
+    } else {
+        a.0.cmp(&(b.0 << (b.1 - a.1)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, HashSet};
+
+    use super::*;
+
+    fn frac(numerator: f64, denominator: f64) -> SafeDecimal<f64> {
+        SafeDecimal {
+            numerator,
+            denominator,
+        }
+    }
+
+    #[test]
+    fn it_compares_values() {
+        assert!(frac(1.0, 3.0) < frac(1.0, 2.0));
+        assert!(frac(-1.0, 3.0) > frac(-1.0, 2.0));
+        assert!(frac(-1.0, 3.0) < frac(0.0, 1.0));
+        assert_eq!(frac(2.0, 6.0), frac(1.0, 3.0));
+        assert_eq!(frac(0.0, 1.0), frac(-0.0, 5.0));
+        assert_eq!(frac(0.25, 0.5), frac(4.0, 8.0));
+    }
+
+    #[test]
+    fn it_compares_values_with_rounded_cross_products() {
+        // (x + 1) / x against x / (x - 1): x^2 and x^2 - 1 can't be told apart as f64
+        let x = 9007199254740990.0; // 2^53 - 2
+        let a = frac(x + 1.0, x);
+        let b = frac(x, x - 1.0);
+        assert!(a < b);
+        assert!(b > a);
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn it_compares_values_with_overflowing_cross_products() {
+        let a = frac(f64::MAX, 2.0);
+        let b = frac(f64::MAX, 3.0);
+        assert!(a > b);
+        assert_ne!(a, b);
+
+        let tiny = frac(f64::MIN_POSITIVE, f64::MAX);
+        assert!(tiny > frac(0.0, 1.0));
+        assert!(-tiny < frac(0.0, 1.0));
+    }
+
+    #[test]
+    fn it_can_be_used_as_a_sorted_key() {
+        let x = 9007199254740990.0;
+        let mut map = BTreeMap::new();
+        map.insert(frac(x, x - 1.0), "b");
+        map.insert(frac(x + 1.0, x), "a");
+        map.insert(frac(1.0, 1.0), "one");
+        assert_eq!(map.values().copied().collect::<Vec<_>>(), ["one", "a", "b"]);
+    }
+
+    #[test]
+    fn it_hashes_equal_values_the_same() {
+        let mut set = HashSet::new();
+        set.insert(frac(3.0, 7.0));
+        assert!(set.contains(&frac(6.0, 14.0)));
+        assert!(set.contains(&frac(-0.75, -1.75)));
+        assert!(!set.contains(&frac(3.0, 8.0)));
+
+        set.insert(frac(0.0, 1.0));
+        assert!(set.contains(&frac(-0.0, 2.0)));
+
+        let sum = SafeDecimal::from(0.1) + SafeDecimal::from(0.2);
+        set.insert(sum);
+        assert!(set.contains(&SafeDecimal::from(0.3)));
+    }
+}
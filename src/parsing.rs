@@ -1,56 +1,577 @@
 use num_traits::float::Float;
-use std::num::ParseIntError;
 
-use crate::{double::construct_float, iter_pad::PadTrait, SafeDecimal};
+use crate::{
+    double::{construct_float, is_f64},
+    format::Separators,
+    iter_pad::PadTrait,
+    SafeDecimal,
+};
 
-pub fn from_integer<T: Float>(integer: &str) -> Result<SafeDecimal<T>, ParseIntError> {
-    let (is_negative, _, integer_numerator) = extract_prefix(integer)?;
+/// Error returned when parsing a [`SafeDecimal`] from a string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseDecimalError {
+    /// There are no digits to parse.
+    Empty,
+    /// The character at this byte position isn't a valid digit for the radix.
+    InvalidDigit { position: usize },
+    /// There's more than one decimal point.
+    MultipleDots,
+    /// The number is too large to be represented.
+    OutOfRange,
+    /// The number can't be represented exactly. Only returned when parsing with
+    /// [`SafeDecimal::from_str_exact`].
+    PrecisionLost,
+    /// The radix isn't supported.
+    UnsupportedRadix(u32),
+    /// The denominator of a fraction is zero.
+    ZeroDenominator,
+    /// The repeating digits starting at this byte position are malformed, or can't be told apart
+    /// when written with an ellipsis.
+    InvalidRepetend { position: usize },
+}
+
+impl std::fmt::Display for ParseDecimalError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseDecimalError::Empty => write!(f, "cannot parse decimal from empty string"),
+            ParseDecimalError::InvalidDigit { position } => {
+                write!(f, "invalid digit found at position {}", position)
+            }
+            ParseDecimalError::MultipleDots => write!(f, "more than one decimal point found"),
+            ParseDecimalError::OutOfRange => write!(f, "number too large to be represented"),
+            ParseDecimalError::PrecisionLost => {
+                write!(f, "number can't be represented without losing precision")
+            }
+            ParseDecimalError::UnsupportedRadix(radix) => {
+                write!(f, "radix {} is not supported", radix)
+            }
+            ParseDecimalError::ZeroDenominator => write!(f, "denominator of the fraction is zero"),
+            ParseDecimalError::InvalidRepetend { position } => {
+                write!(f, "invalid repeating digits at position {}", position)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseDecimalError {}
+
+/// Parses a number such as `-12.34`, `0x1f.8`, `6.02e23`, `-22/7` or `1.2(142857)`.
+///
+/// When `exact` is set, digits that can't be represented fail with `PrecisionLost` instead of
+/// getting rounded away.
+pub fn parse<T: Float + std::fmt::Debug>(
+    value: &str,
+    exact: bool,
+) -> Result<SafeDecimal<T>, ParseDecimalError> {
+    match value.split_once('/') {
+        Some((numerator, denominator)) => parse_fraction(numerator, denominator, exact),
+        None => parse_number(value, exact),
+    }
+}
+
+/// Parses a number written with other separators, such as `1.234.567,89`, by translating them to
+/// the ones `parse` understands. Grouped integer digits have to follow the group sizes.
+pub fn parse_separated<T: Float + std::fmt::Debug>(
+    value: &str,
+    separators: &Separators,
+    exact: bool,
+) -> Result<SafeDecimal<T>, ParseDecimalError> {
+    let integer_end = value.find(separators.decimal).unwrap_or(value.len());
+    if let Some(group) = separators.group {
+        validate_groups(&value[..integer_end], group, separators)?;
+    }
+
+    // Where every byte of the translated string comes from, to report errors at the right place.
+    let mut translated = String::with_capacity(value.len());
+    let mut positions = Vec::with_capacity(value.len());
+    for (position, c) in value.char_indices() {
+        let c = if c == separators.decimal {
+            '.'
+        } else if Some(c) == separators.group && position < integer_end {
+            continue;
+        } else if c == '.' {
+            return Err(ParseDecimalError::InvalidDigit { position });
+        } else {
+            c
+        };
+        translated.push(c);
+        positions.extend(std::iter::repeat(position).take(c.len_utf8()));
+    }
+
+    let original = |position: usize| positions.get(position).copied().unwrap_or(value.len());
+    parse(&translated, exact).map_err(|err| match err {
+        ParseDecimalError::InvalidDigit { position } => ParseDecimalError::InvalidDigit {
+            position: original(position),
+        },
+        ParseDecimalError::InvalidRepetend { position } => ParseDecimalError::InvalidRepetend {
+            position: original(position),
+        },
+        err => err,
+    })
+}
+
+/// Checks that the groups in the integer part have the right sizes. The first one can be shorter.
+fn validate_groups(
+    integer_part: &str,
+    group: char,
+    separators: &Separators,
+) -> Result<(), ParseDecimalError> {
+    let (_, radix, _, _) = extract_prefix(integer_part);
+    let digit_count = |digits: &str| digits.chars().take_while(|c| c.is_digit(radix)).count();
+
+    let mut separators_at = integer_part
+        .match_indices(group)
+        .map(|(position, _)| position);
+    let first = match separators_at.next() {
+        Some(first) => first,
+        None => return Ok(()),
+    };
+    let leading = integer_part[..first]
+        .chars()
+        .rev()
+        .take_while(|c| c.is_digit(radix))
+        .count();
+    if leading == 0 || leading > separators.secondary_group_size {
+        return Err(ParseDecimalError::InvalidDigit { position: first });
+    }
+
+    let mut previous = first;
+    for position in separators_at.chain(std::iter::once(integer_part.len())) {
+        let group_start = previous + group.len_utf8();
+        let digits = digit_count(&integer_part[group_start..]);
+        let is_valid = if position == integer_part.len() {
+            digits == separators.group_size
+        } else {
+            position - group_start == separators.secondary_group_size
+                && digits >= position - group_start
+        };
+        if !is_valid {
+            return Err(ParseDecimalError::InvalidDigit { position: previous });
+        }
+        previous = position;
+    }
+    Ok(())
+}
+
+/// Parses both sides of a fraction. Each one can be any number `parse_number` accepts, surrounded
+/// by whitespace.
+fn parse_fraction<T: Float + std::fmt::Debug>(
+    numerator: &str,
+    denominator: &str,
+    exact: bool,
+) -> Result<SafeDecimal<T>, ParseDecimalError> {
+    let parse_side = |side: &str, offset: usize| {
+        let trimmed = side.trim_start();
+        let offset = offset + side.len() - trimmed.len();
+        parse_number::<T>(trimmed.trim_end(), exact).map_err(|err| match err {
+            ParseDecimalError::InvalidDigit { position } => ParseDecimalError::InvalidDigit {
+                position: offset + position,
+            },
+            err => err,
+        })
+    };
+    let denominator_offset = numerator.len() + 1;
+    let numerator = parse_side(numerator, 0)?;
+    let denominator = parse_side(denominator, denominator_offset)?;
+
+    let denominator = denominator
+        .inv()
+        .ok_or(ParseDecimalError::ZeroDenominator)?;
+    match numerator.mul_exact(denominator) {
+        Ok(result) => Ok(result),
+        Err(_) if exact => Err(ParseDecimalError::PrecisionLost),
+        Err(inexact) => Ok(inexact.value()),
+    }
+}
+
+fn parse_number<T: Float + std::fmt::Debug>(
+    value: &str,
+    exact: bool,
+) -> Result<SafeDecimal<T>, ParseDecimalError> {
+    let (mantissa, exponent) = split_exponent(value)?;
+    let parsed = parse_mantissa(mantissa, exact)?;
+
+    let exponent = match exponent {
+        Some(exponent) => exponent,
+        None => return Ok(parsed),
+    };
+
+    // Moving the decimal point to the end of the digits lets the fractional digits cancel out with
+    // the exponent, e.g. `6.02e23` is better represented as `602 * 10^21` than `6.02 * 10^23`.
+    let result = match mantissa.split_once('.') {
+        Some((integer_part, fractional_part))
+            if integer_part.len() + fractional_part.len() <= 38
+                && !is_repeating(fractional_part) =>
+        {
+            let digits = integer_part.to_owned() + fractional_part;
+            let exponent = exponent.saturating_sub(fractional_part.len() as i32);
+            apply_exponent(parse_parts(&digits, "", exact)?, exponent)
+        }
+        _ => apply_exponent(parsed, exponent),
+    };
+    if let Some(result) = result {
+        return Ok(result);
+    }
+    if exact {
+        return Err(ParseDecimalError::PrecisionLost);
+    }
+
+    // It can only be approximated, and the float parser already knows how to get the closest value.
+    let approximation = match mantissa.split_once('.') {
+        Some((_, fractional_part)) if is_repeating(fractional_part) => {
+            T::from(parsed.to_float().to_f64().unwrap() * 10_f64.powi(exponent))
+        }
+        _ => value.parse::<f64>().ok().and_then(T::from),
+    }
+    .filter(|v| v.is_finite())
+    .ok_or(ParseDecimalError::OutOfRange)?;
+    Ok(SafeDecimal {
+        numerator: approximation,
+        denominator: T::one(),
+    })
+}
+
+fn parse_mantissa<T: Float + std::fmt::Debug>(
+    value: &str,
+    exact: bool,
+) -> Result<SafeDecimal<T>, ParseDecimalError> {
+    match value.split_once('.') {
+        Some((integer_part, fractional_part)) if is_repeating(fractional_part) => {
+            parse_repeating(integer_part, fractional_part, exact)
+        }
+        Some((integer_part, fractional_part)) => {
+            if fractional_part.contains('.') {
+                return Err(ParseDecimalError::MultipleDots);
+            }
+            parse_parts(integer_part, fractional_part, exact)
+        }
+        None => parse_parts(value, "", exact),
+    }
+}
+
+const OVERLINE: char = '\u{305}';
+
+fn is_repeating(fractional_part: &str) -> bool {
+    fractional_part.contains(['(', ')', OVERLINE])
+        || fractional_part.ends_with("...")
+        || fractional_part.ends_with('…')
+}
+
+/// Parses a number with repeating fractional digits, written as `1.2(142857)`, `0.3̅` or `0.333...`.
+fn parse_repeating<T: Float + std::fmt::Debug>(
+    integer_part: &str,
+    fractional_part: &str,
+    exact: bool,
+) -> Result<SafeDecimal<T>, ParseDecimalError> {
+    let (is_negative, radix, _, _) = extract_prefix(integer_part);
+    let offset = integer_part.len() + 1;
+    let (non_repeating, repeating) = split_repetend(fractional_part, radix, offset)?;
+
+    // 0.A(B) = (A + B / (radix^len(B) - 1)) / radix^len(A)
+    let base = parse_parts::<T>(integer_part, &non_repeating, exact)?;
+    let repetend = repetend_value::<T>(&repeating, radix, non_repeating.len());
+    let result = repetend.and_then(|repetend| {
+        if is_negative {
+            base.sub_exact(repetend).ok()
+        } else {
+            base.add_exact(repetend).ok()
+        }
+    });
+
+    match result {
+        Some(result) => Ok(result),
+        None if exact => Err(ParseDecimalError::PrecisionLost),
+        None => {
+            // Too many digits to represent it exactly, but writing out enough repetitions gets the closest value.
+            let mut digits = non_repeating;
+            while digits.len() < 64 {
+                digits += &repeating;
+            }
+            parse_parts(integer_part, &digits, false)
+        }
+    }
+}
+
+/// `0.0...0(B)`, with `shift` zeroes before the repetend. `None` if it can't be represented exactly.
+fn repetend_value<T: Float + std::fmt::Debug>(
+    repeating: &str,
+    radix: u32,
+    shift: usize,
+) -> Option<SafeDecimal<T>> {
+    let integer = |value: u128| {
+        integer_to_float::<T>(value, true)
+            .ok()
+            .map(|value| SafeDecimal {
+                numerator: value,
+                denominator: T::one(),
+            })
+    };
+
+    let numerator = integer(u128::from_str_radix(repeating, radix).ok()?)?;
+    let denominator = integer((radix as u128).checked_pow(repeating.len() as u32)? - 1)?;
+
+    let mut result = numerator.mul_exact(denominator.inv()?).ok()?;
+    let radix = integer(radix as u128)?;
+    for _ in 0..shift {
+        result = result.div_exact(radix).ok()?;
+    }
+    Some(result)
+}
+
+/// Splits the fractional digits into the non-repeating ones and the repetend.
+fn split_repetend(
+    fractional_part: &str,
+    radix: u32,
+    offset: usize,
+) -> Result<(String, String), ParseDecimalError> {
+    if let Some(open) = fractional_part.find('(') {
+        let invalid = Err(ParseDecimalError::InvalidRepetend {
+            position: offset + open,
+        });
+        let repeating = match fractional_part[(open + 1)..].strip_suffix(')') {
+            Some(repeating) if !repeating.is_empty() => repeating,
+            _ => return invalid,
+        };
+        let non_repeating = &fractional_part[..open];
+        if non_repeating.contains(')') {
+            return invalid;
+        }
+        validate_digits(non_repeating, radix, offset)?;
+        validate_digits(repeating, radix, offset + open + 1)?;
+        return Ok((non_repeating.to_owned(), repeating.to_owned()));
+    }
+
+    if fractional_part.contains(OVERLINE) {
+        // Every overlined digit is followed by the combining character, and they have to be the last ones.
+        let mut non_repeating = String::new();
+        let mut repeating = String::new();
+        let mut chars = fractional_part.char_indices().peekable();
+        while let Some((position, c)) = chars.next() {
+            if !c.is_digit(radix) {
+                return Err(ParseDecimalError::InvalidDigit {
+                    position: offset + position,
+                });
+            }
+            if chars.next_if(|(_, next)| *next == OVERLINE).is_some() {
+                repeating.push(c);
+            } else if repeating.is_empty() {
+                non_repeating.push(c);
+            } else {
+                return Err(ParseDecimalError::InvalidRepetend {
+                    position: offset + position,
+                });
+            }
+        }
+        return Ok((non_repeating, repeating));
+    }
+
+    let digits = fractional_part
+        .strip_suffix("...")
+        .or_else(|| fractional_part.strip_suffix('…'))
+        .unwrap_or(fractional_part);
+    validate_digits(digits, radix, offset)?;
+
+    // With an ellipsis, the repetend is the longest group of digits that shows up at least twice at
+    // the end. The shortest one can be wrong: `0.011011…` ends with `11`, but the period is `011`.
+    // A multiple of the period gives the same value, so the longest one is always safe.
+    let digits = digits.as_bytes();
+    let len = digits.len();
+    let period = (1..=(len / 2))
+        .rev()
+        .find(|&p| digits[(len - p)..] == digits[(len - 2 * p)..(len - p)])
+        .ok_or(ParseDecimalError::InvalidRepetend {
+            position: offset + len,
+        })?;
+    let mut start = len - 2 * period;
+    while start > 0 && digits[start - 1] == digits[start - 1 + period] {
+        start -= 1;
+    }
+
+    let digits = std::str::from_utf8(digits).unwrap();
+    Ok((
+        digits[..start].to_owned(),
+        digits[start..(start + period)].to_owned(),
+    ))
+}
+
+/// Splits the decimal exponent (`e-3`, `E+10`) from the rest of the number.
+fn split_exponent(value: &str) -> Result<(&str, Option<i32>), ParseDecimalError> {
+    // `e` is a digit in hexadecimal, so only decimal numbers can have an exponent.
+    let (_, radix, _, _) = extract_prefix(value);
+    if radix != 10 {
+        return Ok((value, None));
+    }
+    let position = match value.find(['e', 'E']) {
+        Some(position) => position,
+        None => return Ok((value, None)),
+    };
+
+    let exponent = &value[(position + 1)..];
+    let digits = exponent.trim_start_matches(['-', '+']);
+    let offset = value.len() - digits.len();
+    if digits.is_empty() || exponent.len() - digits.len() > 1 {
+        // Missing digits or more than one sign: blame the last thing before the digits.
+        return Err(ParseDecimalError::InvalidDigit {
+            position: offset.min(value.len()) - 1,
+        });
+    }
+    validate_digits(digits, 10, offset)?;
+
+    let exponent = exponent
+        .parse::<i32>()
+        .map_err(|_| ParseDecimalError::OutOfRange)?;
+    Ok((&value[..position], Some(exponent)))
+}
+
+/// Multiplies the value by `10^exponent`, or `None` if the result can't be represented exactly.
+fn apply_exponent<T: Float + std::fmt::Debug>(
+    value: SafeDecimal<T>,
+    exponent: i32,
+) -> Option<SafeDecimal<T>> {
+    if value.numerator == T::zero() {
+        return Some(value);
+    }
+
+    // 10^exponent = 5^exponent * 2^exponent. The factors of 2 are free to represent, so this only
+    // becomes inexact once 5^exponent doesn't fit in the mantissa anymore.
+    let ten = SafeDecimal {
+        numerator: T::from(10).unwrap(),
+        denominator: T::one(),
+    };
+    let mut factor = SafeDecimal {
+        numerator: T::one(),
+        denominator: T::one(),
+    };
+    for _ in 0..exponent.unsigned_abs() {
+        factor = factor.mul_exact(ten).ok()?;
+    }
+
+    if exponent > 0 {
+        value.mul_exact(factor).ok()
+    } else {
+        value.div_exact(factor).ok()
+    }
+}
+
+pub fn from_integer<T: Float>(integer: &str) -> Result<SafeDecimal<T>, ParseDecimalError> {
+    let (is_negative, radix, digits, offset) = extract_prefix(integer);
+    if digits.is_empty() {
+        return Err(ParseDecimalError::Empty);
+    }
 
     // TODO if the number is out of range for f32/f64, maybe reducing it beforehand by increasing the denominator could help
     // Also this function covers a subset of `from_parts`... I would delete it
     // but then this todo bring it also to `from_parts`
     let parsed = SafeDecimal {
-        numerator: T::from(integer_numerator).unwrap(),
+        numerator: integer_to_float(parse_integer(digits, radix, offset)?, false)?,
         denominator: T::from(1).unwrap(),
     };
     if is_negative {
         return Ok(-parsed);
     }
-    return Ok(parsed);
+    Ok(parsed)
 }
 
 // TODO it's confusing having 2 `from_parts` functions
 pub fn from_parts<T: Float + std::fmt::Debug>(
     integer_part: &str,
     fractional_part: &str,
-) -> Result<SafeDecimal<T>, ParseIntError> {
-    let (is_negative, radix, integer_numerator) = extract_prefix(integer_part)?;
+) -> Result<SafeDecimal<T>, ParseDecimalError> {
+    parse_parts(integer_part, fractional_part, false)
+}
+
+fn parse_parts<T: Float + std::fmt::Debug>(
+    integer_part: &str,
+    fractional_part: &str,
+    exact: bool,
+) -> Result<SafeDecimal<T>, ParseDecimalError> {
+    let (is_negative, radix, digits, offset) = extract_prefix(integer_part);
+    parse_digits(is_negative, radix, digits, offset, fractional_part, exact)
+}
+
+/// Parses a number in any radix from 2 to 36, such as `-z.i` in base 36. It takes an optional sign,
+/// but no prefix or exponent.
+pub fn parse_radix<T: Float + std::fmt::Debug>(
+    value: &str,
+    radix: u32,
+    exact: bool,
+) -> Result<SafeDecimal<T>, ParseDecimalError> {
+    if !(2..=36).contains(&radix) {
+        return Err(ParseDecimalError::UnsupportedRadix(radix));
+    }
+
+    let (is_negative, digits) = split_sign(value);
+    let offset = value.len() - digits.len();
+    let (integer_digits, fractional_part) = match digits.split_once('.') {
+        Some((_, fractional_part)) if fractional_part.contains('.') => {
+            return Err(ParseDecimalError::MultipleDots)
+        }
+        Some(parts) => parts,
+        None => (digits, ""),
+    };
+    parse_digits(
+        is_negative,
+        radix,
+        integer_digits,
+        offset,
+        fractional_part,
+        exact,
+    )
+}
+
+fn parse_digits<T: Float + std::fmt::Debug>(
+    is_negative: bool,
+    radix: u32,
+    digits: &str,
+    offset: usize,
+    fractional_part: &str,
+    exact: bool,
+) -> Result<SafeDecimal<T>, ParseDecimalError> {
+    if digits.is_empty() && fractional_part.is_empty() {
+        return Err(ParseDecimalError::Empty);
+    }
+
+    let integer_numerator = parse_integer(digits, radix, offset)?;
     let integer_part_rational = SafeDecimal {
-        numerator: T::from(integer_numerator).unwrap(),
+        numerator: integer_to_float(integer_numerator, exact)?,
         denominator: T::from(1).unwrap(),
     };
 
+    // Positions are relative to the start of the integer part, skipping the dot.
+    let offset = offset + digits.len() + 1;
     let fractional_part_rational = match radix {
-        2 => fractional_part_2(fractional_part),
-        8 => fractional_part_8(fractional_part),
-        10 => fractional_part_10(fractional_part),
-        16 => fractional_part_16(fractional_part),
-        _ => unreachable!(),
+        10 => fractional_part_10(fractional_part, offset, exact),
+        radix if radix.is_power_of_two() => {
+            fractional_part_pow2(fractional_part, radix, offset, exact)
+        }
+        radix => fractional_part_radix(fractional_part, radix, offset, exact),
     }?;
 
-    let parsed = integer_part_rational + fractional_part_rational;
+    // Reduced, the fraction is less likely to overflow the mantissa when both parts get added.
+    let fractional_part_rational = fractional_part_rational.normalize();
+    let parsed = match integer_part_rational.add_exact(fractional_part_rational) {
+        Ok(parsed) => parsed,
+        Err(_) if exact => return Err(ParseDecimalError::PrecisionLost),
+        Err(inexact) => inexact.value(),
+    };
     if is_negative {
         return Ok(-parsed);
     }
-    return Ok(parsed);
+    Ok(parsed)
 }
 
 pub fn fractional_part_10<T: Float>(
     fractional_part: &str,
-) -> Result<SafeDecimal<T>, ParseIntError> {
-    // 5^22 < MAX_SAFE_INTEGER for f64, meaning past this point we're doomed to lose precision.
+    offset: usize,
+    exact: bool,
+) -> Result<SafeDecimal<T>, ParseDecimalError> {
+    validate_digits(fractional_part, 10, offset)?;
 
+    // 5^22 < MAX_SAFE_INTEGER for f64, meaning past this point we're doomed to lose precision.
     let len = fractional_part.len().min(22);
+    if exact && fractional_part[len..].contains(|c| c != '0') {
+        return Err(ParseDecimalError::PrecisionLost);
+    }
     if len == 0 {
         return Ok(SafeDecimal {
             numerator: T::zero(),
@@ -61,18 +582,28 @@ pub fn fractional_part_10<T: Float>(
     let denominator = T::from(5)
         .unwrap()
         .powf(T::from(fractional_part.len()).unwrap());
+    if exact && denominator.to_u128() != Some(5_u128.pow(len as u32)) {
+        return Err(ParseDecimalError::PrecisionLost);
+    }
 
     let correction = T::from(2)
         .unwrap()
         .powf(T::from(fractional_part.len()).unwrap());
-    let numerator = T::from(u128::from_str_radix(fractional_part, 10)?).unwrap() / correction;
+    let numerator =
+        integer_to_float::<T>(parse_integer(fractional_part, 10, offset)?, exact)? / correction;
     Ok(SafeDecimal {
         numerator,
         denominator,
     })
 }
 
-pub fn fractional_part_2<T: Float>(fractional_part: &str) -> Result<SafeDecimal<T>, ParseIntError> {
+pub fn fractional_part_2<T: Float>(
+    fractional_part: &str,
+    offset: usize,
+    exact: bool,
+) -> Result<SafeDecimal<T>, ParseDecimalError> {
+    validate_digits(fractional_part, 2, offset)?;
+
     let first_one = fractional_part.find('1');
     if first_one.is_none() {
         return Ok(SafeDecimal {
@@ -85,12 +616,16 @@ pub fn fractional_part_2<T: Float>(fractional_part: &str) -> Result<SafeDecimal<
     let (fractional_part, exponent) = first_one
         .map(|pos| (&fractional_part[(pos + 1)..], -(pos as i16 + 1)))
         .unwrap();
+    let mantissa_len = if is_f64::<T>() { 52 } else { 23 };
+    if exact && fractional_part.chars().skip(mantissa_len).any(|c| c == '1') {
+        return Err(ParseDecimalError::PrecisionLost);
+    }
 
     let mantissa = fractional_part
         .chars()
-        .take(52)
+        .take(mantissa_len)
         .map(|v| if v == '0' { 0 } else { 1 })
-        .pad(52, 0)
+        .pad(mantissa_len, 0)
         .fold(0_u64, |acc, x| acc << 1 | x);
 
     Ok(SafeDecimal {
@@ -99,70 +634,125 @@ pub fn fractional_part_2<T: Float>(fractional_part: &str) -> Result<SafeDecimal<
     })
 }
 
-pub fn fractional_part_8<T: Float>(fractional_part: &str) -> Result<SafeDecimal<T>, ParseIntError> {
+/// Fractional digits in a radix that is a power of two, which map to a fixed number of bits each.
+pub fn fractional_part_pow2<T: Float>(
+    fractional_part: &str,
+    radix: u32,
+    offset: usize,
+    exact: bool,
+) -> Result<SafeDecimal<T>, ParseDecimalError> {
+    validate_digits(fractional_part, radix, offset)?;
+
+    let bits = radix.trailing_zeros() as usize;
     let binary_rep = fractional_part
         .chars()
-        .map(|c| match c {
-            '0' => "000",
-            '1' => "001",
-            '2' => "010",
-            '3' => "011",
-            '4' => "100",
-            '5' => "101",
-            '6' => "110",
-            '7' => "111",
-            _ => todo!("Throw ParseIntError"),
-        })
-        .fold(String::new(), |acc, v| acc + v);
+        .map(|c| format!("{:0bits$b}", c.to_digit(radix).unwrap(), bits = bits))
+        .collect::<String>();
 
-    fractional_part_2(&binary_rep)
+    fractional_part_2(&binary_rep, 0, exact)
 }
 
-pub fn fractional_part_16<T: Float>(
+/// Fractional digits in a radix with odd prime factors, as `digits / radix^len`. Those factors stay
+/// in the denominator, so only a few digits can be represented exactly. Past that, it's rounded.
+pub fn fractional_part_radix<T: Float>(
     fractional_part: &str,
-) -> Result<SafeDecimal<T>, ParseIntError> {
-    let binary_rep = fractional_part
-        .chars()
-        .map(|c| match c.to_ascii_lowercase() {
-            '0' => "0000",
-            '1' => "0001",
-            '2' => "0010",
-            '3' => "0011",
-            '4' => "0100",
-            '5' => "0101",
-            '6' => "0110",
-            '7' => "0111",
-            '8' => "1000",
-            '9' => "1001",
-            'a' => "1010",
-            'b' => "1011",
-            'c' => "1100",
-            'd' => "1101",
-            'e' => "1110",
-            'f' => "1111",
-            _ => todo!("Throw ParseIntError"),
-        })
-        .fold(String::new(), |acc, v| acc + v);
+    radix: u32,
+    offset: usize,
+    exact: bool,
+) -> Result<SafeDecimal<T>, ParseDecimalError> {
+    validate_digits(fractional_part, radix, offset)?;
+
+    let fractional_part = fractional_part.trim_end_matches('0');
+    if fractional_part.is_empty() {
+        return Ok(SafeDecimal {
+            numerator: T::zero(),
+            denominator: T::one(),
+        });
+    }
+
+    // radix^len = odd^len * 2^(twos * len), where only the odd part needs to fit in the mantissa.
+    let len = fractional_part.len() as u32;
+    let twos = radix.trailing_zeros();
+    let odd = (radix >> twos) as u128;
+    let exact_value = u128::from_str_radix(fractional_part, radix)
+        .ok()
+        .zip(odd.checked_pow(len))
+        .and_then(|(numerator, denominator)| {
+            let numerator = integer_to_float::<T>(numerator, true).ok()?;
+            let denominator = integer_to_float::<T>(denominator, true).ok()?;
+            Some(SafeDecimal {
+                numerator: numerator / T::from(2).unwrap().powi((twos * len) as i32),
+                denominator,
+            })
+        });
 
-    fractional_part_2(&binary_rep)
+    match exact_value {
+        Some(value) => Ok(value),
+        None if exact => Err(ParseDecimalError::PrecisionLost),
+        None => {
+            let radix = T::from(radix).unwrap();
+            let numerator = fractional_part
+                .chars()
+                .rev()
+                .map(|c| T::from(c.to_digit(36).unwrap()).unwrap())
+                .fold(T::zero(), |acc, digit| (acc + digit) / radix);
+            Ok(SafeDecimal {
+                numerator,
+                denominator: T::one(),
+            })
+        }
+    }
 }
 
-fn extract_prefix(value: &str) -> Result<(bool, u32, u128), ParseIntError> {
-    let is_negative = value.starts_with("-");
-    let value = value.trim_start_matches(|c| c == '-' || c == '+');
-    let len = value.len().min(2);
-    let radix = match &value[..len] {
-        "0b" => 2,
-        "0o" => 8,
-        "0x" => 16,
-        _ => 10,
+/// Splits the sign and the radix prefix from the digits. Also returns the position where the digits start.
+fn extract_prefix(value: &str) -> (bool, u32, &str, usize) {
+    let (is_negative, digits) = split_sign(value);
+    let (radix, digits) = match digits.get(..2) {
+        Some("0b") => (2, &digits[2..]),
+        Some("0o") => (8, &digits[2..]),
+        Some("0x") => (16, &digits[2..]),
+        _ => (10, digits),
     };
-    let value = if radix == 10 { value } else { &value[2..] };
 
-    Ok((is_negative, radix, u128::from_str_radix(value, radix)?))
+    (is_negative, radix, digits, value.len() - digits.len())
+}
+
+fn split_sign(value: &str) -> (bool, &str) {
+    match value.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, value.strip_prefix('+').unwrap_or(value)),
+    }
+}
+
+fn validate_digits(digits: &str, radix: u32, offset: usize) -> Result<(), ParseDecimalError> {
+    match digits.char_indices().find(|(_, c)| !c.is_digit(radix)) {
+        Some((position, _)) => Err(ParseDecimalError::InvalidDigit {
+            position: offset + position,
+        }),
+        None => Ok(()),
+    }
+}
+
+fn parse_integer(digits: &str, radix: u32, offset: usize) -> Result<u128, ParseDecimalError> {
+    validate_digits(digits, radix, offset)?;
+    if digits.is_empty() {
+        return Ok(0);
+    }
+    u128::from_str_radix(digits, radix).map_err(|_| ParseDecimalError::OutOfRange)
+}
+
+fn integer_to_float<T: Float>(value: u128, exact: bool) -> Result<T, ParseDecimalError> {
+    let result = T::from(value)
+        .filter(|v| v.is_finite())
+        .ok_or(ParseDecimalError::OutOfRange)?;
+    if exact && result.to_u128() != Some(value) {
+        return Err(ParseDecimalError::PrecisionLost);
+    }
+    Ok(result)
 }
 
 #[cfg(test)]
+#[allow(clippy::excessive_precision)]
 mod tests {
     use super::*;
 
@@ -201,6 +791,390 @@ mod tests {
         check_parsing("+0b10", "0000000001", 2.0009765625);
     }
 
+    fn check_error(value: &str, error: ParseDecimalError) {
+        assert_eq!(parse::<f64>(value, false).unwrap_err(), error);
+    }
+
+    #[test]
+    fn it_reports_invalid_input() {
+        check_error("", ParseDecimalError::Empty);
+        check_error("-", ParseDecimalError::Empty);
+        check_error("0x", ParseDecimalError::Empty);
+        check_error(".", ParseDecimalError::Empty);
+        check_error("1.2.3", ParseDecimalError::MultipleDots);
+        check_error("12a", ParseDecimalError::InvalidDigit { position: 2 });
+        check_error("--1", ParseDecimalError::InvalidDigit { position: 1 });
+        check_error("+-1", ParseDecimalError::InvalidDigit { position: 1 });
+        check_error("é", ParseDecimalError::InvalidDigit { position: 0 });
+        check_error("1.2x", ParseDecimalError::InvalidDigit { position: 3 });
+        check_error("-0x1g", ParseDecimalError::InvalidDigit { position: 4 });
+        check_error("0o1.9", ParseDecimalError::InvalidDigit { position: 4 });
+        check_error("0b1.12", ParseDecimalError::InvalidDigit { position: 5 });
+        check_error("0x0.fg", ParseDecimalError::InvalidDigit { position: 5 });
+        check_error(&"9".repeat(40), ParseDecimalError::OutOfRange);
+        assert_eq!(
+            parse::<f32>(&u128::MAX.to_string(), false).unwrap_err(),
+            ParseDecimalError::OutOfRange
+        );
+    }
+
+    #[test]
+    fn it_accepts_missing_integer_or_fractional_digits() {
+        assert_eq!(parse::<f64>(".5", false).unwrap().to_float(), 0.5);
+        assert_eq!(parse::<f64>("-.5", false).unwrap().to_float(), -0.5);
+        assert_eq!(parse::<f64>("3.", false).unwrap().to_float(), 3.0);
+    }
+
+    #[test]
+    fn it_reports_lost_precision_when_parsing_exactly() {
+        assert!(parse::<f64>("0.1", true).is_ok());
+        assert!(parse::<f64>("9007199254740992", true).is_ok());
+        assert!(parse::<f64>("0.5000000000000000000000000", true).is_ok());
+        assert!(parse::<f64>("0x0.8000000000001", true).is_ok());
+
+        let lost = ParseDecimalError::PrecisionLost;
+        assert_eq!(parse::<f64>("9007199254740993", true).unwrap_err(), lost);
+        assert_eq!(
+            parse::<f64>("0.12345678901234567890123", true).unwrap_err(),
+            lost
+        );
+        assert_eq!(parse::<f64>("0x0.80000000000001", true).unwrap_err(), lost);
+        assert_eq!(parse::<f32>("16777217", true).unwrap_err(), lost);
+        // Integer and fractional parts that are exact on their own, but not their sum
+        assert_eq!(parse::<f64>("9007199254740991.5", true).unwrap_err(), lost);
+        assert_eq!(parse::<f64>("4503599627370497.5", true).unwrap_err(), lost);
+        assert_eq!(parse::<f64>("123456789012345.123", true).unwrap_err(), lost);
+        assert_eq!(
+            parse::<f64>("4503599627370495.5", true).unwrap(),
+            SafeDecimal::new(9007199254740991.0, 2.0).unwrap()
+        );
+
+        // The default parser rounds them instead
+        assert!(parse::<f64>("9007199254740993", false).is_ok());
+    }
+
+    #[test]
+    fn it_parses_fractional_digits_in_f32() {
+        check_parsing("0x0", "c", 0.75_f32);
+        check_parsing("0b1", "0101", 1.3125_f32);
+        assert!(parse::<f32>("0x0.ffffff", true).is_ok());
+        assert_eq!(
+            parse::<f32>("0x0.ffffff8", true).unwrap_err(),
+            ParseDecimalError::PrecisionLost
+        );
+    }
+
+    #[test]
+    fn it_parses_any_radix() {
+        let third = SafeDecimal::new(1.0, 3.0).unwrap();
+        assert_eq!(parse_radix::<f64>("0.1", 3, true).unwrap(), third);
+        assert_eq!(parse_radix::<f64>("-0.2", 6, true).unwrap(), -third);
+        assert_eq!(
+            parse_radix::<f64>("10.6", 12, true).unwrap().to_float(),
+            12.5
+        );
+        assert_eq!(
+            parse_radix::<f64>("Z.i", 36, true).unwrap().to_float(),
+            35.5
+        );
+        assert_eq!(
+            parse_radix::<f64>("11.01", 4, true).unwrap().to_float(),
+            5.0625
+        );
+        assert_eq!(
+            parse_radix::<f64>("v.g", 32, true).unwrap().to_float(),
+            31.5
+        );
+        assert_eq!(
+            parse_radix::<f64>("0.35", 10, true).unwrap().to_float(),
+            0.35
+        );
+        assert_eq!(
+            parse_radix::<f64>("0.0121", 3, true).unwrap(),
+            SafeDecimal::new(16.0, 81.0).unwrap()
+        );
+    }
+
+    #[test]
+    fn it_rounds_fractional_digits_that_dont_fit_in_any_radix() {
+        let digits = "0.".to_owned() + &"1".repeat(40);
+        assert_eq!(
+            parse_radix::<f64>(&digits, 3, true).unwrap_err(),
+            ParseDecimalError::PrecisionLost
+        );
+        assert_eq!(
+            parse_radix::<f64>(&digits, 3, false).unwrap().to_float(),
+            0.5
+        );
+        let digits = "0.".to_owned() + &"4".repeat(40);
+        let parsed = parse_radix::<f64>(&digits, 12, false).unwrap().to_float();
+        assert!((parsed - 4.0 / 11.0).abs() <= f64::EPSILON);
+    }
+
+    #[test]
+    fn it_reports_invalid_input_in_any_radix() {
+        assert_eq!(
+            parse_radix::<f64>("1", 37, false).unwrap_err(),
+            ParseDecimalError::UnsupportedRadix(37)
+        );
+        assert_eq!(
+            parse_radix::<f64>("1", 1, false).unwrap_err(),
+            ParseDecimalError::UnsupportedRadix(1)
+        );
+        assert_eq!(
+            parse_radix::<f64>("-12.03", 3, false).unwrap_err(),
+            ParseDecimalError::InvalidDigit { position: 5 }
+        );
+        assert_eq!(
+            parse_radix::<f64>("0x1", 16, false).unwrap_err(),
+            ParseDecimalError::InvalidDigit { position: 1 }
+        );
+        assert_eq!(
+            parse_radix::<f64>("1.2.3", 5, false).unwrap_err(),
+            ParseDecimalError::MultipleDots
+        );
+        assert_eq!(
+            parse_radix::<f64>("-", 5, false).unwrap_err(),
+            ParseDecimalError::Empty
+        );
+    }
+
+    #[test]
+    fn it_parses_with_separators() {
+        let check = |value: &str, separators: Separators, output: f64| {
+            assert_eq!(
+                parse_separated::<f64>(value, &separators, false)
+                    .unwrap()
+                    .to_float(),
+                output
+            );
+        };
+        let us = Separators::default().group(',');
+        check("1,234,567.89", us.clone(), 1234567.89);
+        check("-1,234", us.clone(), -1234.0);
+        check("234.5", us.clone(), 234.5);
+        check("1,234e2", us.clone(), 123400.0);
+        check("1,234/2", us, 617.0);
+        check(
+            "1.234.567,89",
+            Separators::default().decimal(',').group('.'),
+            1234567.89,
+        );
+        check(
+            "1 234 567,89",
+            Separators::default().decimal(',').group(' '),
+            1234567.89,
+        );
+        check(
+            "12,34,567.89",
+            Separators::default().group(',').group_sizes(3, 2),
+            1234567.89,
+        );
+        check(
+            "0xdead_beef",
+            Separators::default().group('_').group_sizes(4, 4),
+            3735928559.0,
+        );
+        check("1,5", Separators::default().decimal(','), 1.5);
+    }
+
+    #[test]
+    fn it_reports_misplaced_separators() {
+        let check = |value: &str, separators: &Separators, position: usize| {
+            assert_eq!(
+                parse_separated::<f64>(value, separators, false).unwrap_err(),
+                ParseDecimalError::InvalidDigit { position }
+            );
+        };
+        let us = Separators::default().group(',');
+        check("1,23,456", &us, 1);
+        check("1234,567", &us, 4);
+        check("1,234,56", &us, 5);
+        check(",234", &us, 0);
+        check("1,234.5,6", &us, 7);
+        check(
+            "12,34,567",
+            &Separators::default().group(',').group_sizes(3, 3),
+            2,
+        );
+        check("1.5", &Separators::default().decimal(','), 1);
+        check(
+            "1.234,5x",
+            &Separators::default().decimal(',').group('.'),
+            7,
+        );
+        assert_eq!(
+            parse_separated::<f64>("1,2,3", &Separators::default().decimal(','), false)
+                .unwrap_err(),
+            ParseDecimalError::MultipleDots
+        );
+    }
+
+    fn check_str<T: Float + std::fmt::Debug>(value: &str, output: T) {
+        assert_eq!(parse::<T>(value, false).unwrap().to_float(), output);
+    }
+
+    #[test]
+    fn it_parses_scientific_notation() {
+        check_str("1e-7", 1e-7);
+        check_str("1.5e-3", 0.0015);
+        check_str("-2E+10", -2e10);
+        check_str("6.02E23", 6.02e23);
+        check_str("1.2345e2", 123.45);
+        check_str("0e500", 0.0);
+        check_str("1e300", 1e300);
+        check_str("1e-300", 1e-300);
+        check_str("1.7976931348623157e308", f64::MAX);
+        check_str("1e-400", 0.0);
+        check_str("2.5e-3", 0.0025_f32);
+
+        assert_eq!(
+            parse::<f64>("1.5e-3", false).unwrap(),
+            parse::<f64>("0.0015", false).unwrap()
+        );
+        assert_eq!(
+            parse::<f64>("-12.5e3", false).unwrap(),
+            parse::<f64>("-12500", false).unwrap()
+        );
+
+        // `e` is a digit in hexadecimal
+        check_str("0x1e2", 482.0);
+    }
+
+    #[test]
+    fn it_reports_invalid_exponents() {
+        check_error("1e", ParseDecimalError::InvalidDigit { position: 1 });
+        check_error("1e+", ParseDecimalError::InvalidDigit { position: 2 });
+        check_error("1e+-2", ParseDecimalError::InvalidDigit { position: 3 });
+        check_error("1e2.5", ParseDecimalError::InvalidDigit { position: 3 });
+        check_error("1e2e3", ParseDecimalError::InvalidDigit { position: 3 });
+        check_error("e5", ParseDecimalError::Empty);
+        check_error("1e99999999999", ParseDecimalError::OutOfRange);
+        check_error("1e1000", ParseDecimalError::OutOfRange);
+
+        assert!(parse::<f64>("1.5e-3", true).is_ok());
+        assert!(parse::<f64>("1e22", true).is_ok());
+        assert_eq!(
+            parse::<f64>("1e-400", true).unwrap_err(),
+            ParseDecimalError::PrecisionLost
+        );
+        assert_eq!(
+            parse::<f64>("3e-40", true).unwrap_err(),
+            ParseDecimalError::PrecisionLost
+        );
+    }
+
+    #[test]
+    fn it_parses_fractions() {
+        let third = parse::<f64>("1/3", false).unwrap();
+        assert_eq!(third.numerator, 1.0);
+        assert_eq!(third.denominator, 3.0);
+
+        assert_eq!(
+            parse::<f64>("-22/7", false).unwrap(),
+            -parse::<f64>("22/7", false).unwrap()
+        );
+        assert_eq!(
+            parse::<f64>(" 22 / -7 ", false).unwrap(),
+            parse::<f64>("-22/7", false).unwrap()
+        );
+        assert_eq!(
+            parse::<f64>("0x10/0b11", false).unwrap(),
+            parse::<f64>("16/3", false).unwrap()
+        );
+        assert_eq!(
+            parse::<f64>("6/4", false).unwrap(),
+            parse::<f64>("1.5", false).unwrap()
+        );
+        assert_eq!(
+            parse::<f64>("0.1/3", false).unwrap(),
+            parse::<f64>("1/30", false).unwrap()
+        );
+        check_str("1/3", 1.0 / 3.0);
+        check_str("-22/7", -22.0 / 7.0);
+        check_str("0/5", 0.0);
+    }
+
+    #[test]
+    fn it_reports_invalid_fractions() {
+        check_error("1/0", ParseDecimalError::ZeroDenominator);
+        check_error("1/", ParseDecimalError::Empty);
+        check_error("/2", ParseDecimalError::Empty);
+        check_error("1/2/3", ParseDecimalError::InvalidDigit { position: 3 });
+        check_error("1 / 2x", ParseDecimalError::InvalidDigit { position: 5 });
+        check_error("1x / 2", ParseDecimalError::InvalidDigit { position: 1 });
+        check_error(" 1 2/3", ParseDecimalError::InvalidDigit { position: 2 });
+        check_error(
+            "1 /  0x1.g",
+            ParseDecimalError::InvalidDigit { position: 9 },
+        );
+    }
+
+    fn check_same(a: &str, b: &str) {
+        assert_eq!(
+            parse::<f64>(a, false).unwrap(),
+            parse::<f64>(b, false).unwrap()
+        );
+    }
+
+    #[test]
+    fn it_parses_repeating_decimals() {
+        check_same("0.(3)", "1/3");
+        check_same("0.(142857)", "1/7");
+        check_same("-0.1(6)", "-1/6");
+        check_same("8.(3)", "25/3");
+        check_same("0.(9)", "1");
+        check_same("0.3\u{305}", "1/3");
+        check_same("0.16\u{305}", "1/6");
+        check_same("0.1\u{305}4\u{305}2\u{305}8\u{305}5\u{305}7\u{305}", "1/7");
+        check_same("0.333...", "1/3");
+        check_same("0.1666…", "1/6");
+        check_same("0.142857142857...", "1/7");
+        check_same("2.1212...", "70/33");
+        check_same("0.011011…", "11/999");
+        check_same("0x0.(5)", "1/3");
+        check_same("0b0.(01)", "1/3");
+        check_same("0o0.(2)", "2/7");
+        check_same("0.(3)e1", "10/3");
+
+        assert_eq!(
+            parse::<f64>("1.2(142857)", false).unwrap().to_float(),
+            1.2 + 1.0 / 70.0
+        );
+        assert!(parse::<f64>("0.(3)", true).is_ok());
+    }
+
+    #[test]
+    fn it_reports_invalid_repeating_decimals() {
+        check_error("0.(3", ParseDecimalError::InvalidRepetend { position: 2 });
+        check_error("0.1()", ParseDecimalError::InvalidRepetend { position: 3 });
+        check_error("0.1)3(", ParseDecimalError::InvalidRepetend { position: 5 });
+        check_error("0.(3)4", ParseDecimalError::InvalidRepetend { position: 2 });
+        check_error("0.(3a)", ParseDecimalError::InvalidDigit { position: 4 });
+        check_error(
+            "0.3\u{305}4",
+            ParseDecimalError::InvalidRepetend { position: 5 },
+        );
+        check_error("0.\u{305}", ParseDecimalError::InvalidDigit { position: 2 });
+        check_error(
+            "3.14159...",
+            ParseDecimalError::InvalidRepetend { position: 7 },
+        );
+        check_error("0.3x3...", ParseDecimalError::InvalidDigit { position: 3 });
+    }
+
+    #[test]
+    fn it_approximates_long_repetends() {
+        // 1/97 has a period of 96 digits, way more than what fits in a float
+        let digits = "010309278350515463917525773195876288659793814432989690721649484536082474226804123711340206185567";
+        let value = parse::<f64>(&format!("0.({})", digits), false).unwrap();
+        assert_eq!(value.to_float(), 1.0 / 97.0);
+        assert_eq!(
+            parse::<f64>(&format!("0.({})", digits), true).unwrap_err(),
+            ParseDecimalError::PrecisionLost
+        );
+    }
+
     #[test]
     fn it_approximates_numbers_with_large_decimals() {
         // println!("{:e}", 123456.1);
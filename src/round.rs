@@ -0,0 +1,506 @@
+use num_traits::Float;
+
+use crate::{
+    big_uint::BigUint,
+    format::{from_integers, to_integers, Rounding},
+    Inexact, SafeDecimal,
+};
+
+impl<T: Float + std::fmt::Debug> SafeDecimal<T> {
+    /// Rounds to `decimals` decimal places, e.g. `price.round_dp(2, Rounding::HALF_EVEN)`.
+    ///
+    /// The result is exact as long as it can be represented, like every other operation, and an
+    /// approximation otherwise.
+    pub fn round_dp(self, decimals: u32, rounding: Rounding) -> SafeDecimal<T> {
+        let (numerator, denominator) = to_integers(self.numerator, self.denominator);
+        let is_negative = (self.numerator < T::zero()) != (self.denominator < T::zero());
+
+        // round(|value| * 10^decimals), with integers so that it works at any scale.
+        let mut scaled = numerator;
+        for _ in 0..decimals {
+            scaled.mul_small(10);
+        }
+        let mut integer = round_quotient(&scaled, &denominator, is_negative, &rounding);
+
+        // integer / (5^decimals * 2^decimals), without the factors they have in common so that it
+        // stays exact whenever the result can be represented.
+        let (mut fives, mut twos) = (decimals, decimals);
+        while twos > 0 && !integer.is_zero() && !integer.is_odd() {
+            integer.div_rem_small(2);
+            twos -= 1;
+        }
+        while fives > 0 && !integer.is_zero() {
+            let mut divided = integer.clone();
+            if divided.div_rem_small(5) != 0 {
+                break;
+            }
+            integer = divided;
+            fives -= 1;
+        }
+
+        let mut scale = BigUint::from_shifted(1, twos);
+        for _ in 0..fives {
+            scale.mul_small(5);
+        }
+        let result = from_integers::<T>(&integer, &scale).unwrap_or_else(Inexact::value);
+        if is_negative {
+            -result
+        } else {
+            result
+        }
+    }
+
+    /// Rounds to a multiple of `increment`, like 0.05 for cash or 0.25 for quarter hours.
+    ///
+    /// # Panics
+    ///
+    /// If `increment` is zero.
+    pub fn round_to_increment(
+        self,
+        increment: SafeDecimal<T>,
+        rounding: Rounding,
+    ) -> SafeDecimal<T> {
+        assert!(
+            increment.numerator != T::zero(),
+            "the increment can't be zero"
+        );
+        let increment = increment.abs();
+        let (numerator, denominator) = to_integers(self.numerator, self.denominator);
+        let (increment_numerator, increment_denominator) =
+            to_integers(increment.numerator, increment.denominator);
+        let is_negative = (self.numerator < T::zero()) != (self.denominator < T::zero());
+
+        // round(|value| / increment), with integers like round_dp.
+        let multiple = round_quotient(
+            &numerator.mul(&increment_denominator),
+            &denominator.mul(&increment_numerator),
+            is_negative,
+            &rounding,
+        );
+        let result =
+            from_integers::<T>(&multiple.mul(&increment_numerator), &increment_denominator)
+                .unwrap_or_else(Inexact::value);
+        if is_negative {
+            -result
+        } else {
+            result
+        }
+    }
+
+    /// Rounds to an integer with the given rounding.
+    pub fn round_to(self, rounding: Rounding) -> SafeDecimal<T> {
+        round_integer(self, &rounding)
+    }
+
+    /// Largest integer less than or equal to the value.
+    pub fn floor(self) -> SafeDecimal<T> {
+        self.round_to(Rounding::FLOOR)
+    }
+
+    /// Smallest integer greater than or equal to the value.
+    pub fn ceil(self) -> SafeDecimal<T> {
+        self.round_to(Rounding::CEIL)
+    }
+
+    /// Integer part of the value, rounding towards zero.
+    pub fn trunc(self) -> SafeDecimal<T> {
+        self.round_to(Rounding::DOWN)
+    }
+
+    /// Nearest integer, rounding half-way cases away from zero like `f64::round` does.
+    pub fn round(self) -> SafeDecimal<T> {
+        self.round_to(Rounding::HALF_UP)
+    }
+
+    /// Fractional part of the value, with the same sign as the value.
+    pub fn fract(self) -> SafeDecimal<T> {
+        let (numerator, denominator) = to_integers(self.numerator, self.denominator);
+        let (integer, remainder) = numerator.div_rem(&denominator);
+        if integer.is_zero() {
+            return self;
+        }
+
+        let fract = from_integers::<T>(&remainder, &denominator).unwrap_or_else(Inexact::value);
+        if (self.numerator < T::zero()) != (self.denominator < T::zero()) {
+            -fract
+        } else {
+            fract
+        }
+    }
+}
+
+/// `numerator / denominator` rounded to an integer, where `is_negative` is the sign of the
+/// fraction.
+fn round_quotient(
+    numerator: &BigUint,
+    denominator: &BigUint,
+    is_negative: bool,
+    rounding: &Rounding,
+) -> BigUint {
+    let (mut integer, remainder) = numerator.div_rem(denominator);
+    if !remainder.is_zero() {
+        let last_digit = integer.clone().div_rem_small(10);
+        if rounding.should_increment(is_negative, last_digit, &remainder, denominator) {
+            integer.increment();
+        }
+    }
+    integer
+}
+
+/// Rounds the value to an integer.
+pub fn round_integer<T: Float + std::fmt::Debug>(
+    value: SafeDecimal<T>,
+    rounding: &Rounding,
+) -> SafeDecimal<T> {
+    value.round_dp(0, rounding.clone())
+}
+
+/// Rounds the value so that it has at most `digits` significant decimal digits.
+pub fn round_significant<T: Float + std::fmt::Debug>(
+    value: SafeDecimal<T>,
+    digits: usize,
+    rounding: &Rounding,
+) -> SafeDecimal<T> {
+    if value.numerator == T::zero() {
+        return value;
+    }
+
+    // Move the decimal point so that the digits we want to keep are the integer part.
+    let scale = pow10::<T>(digits as i32 - 1 - decimal_exponent(value));
+    round_integer(value * scale, rounding) / scale
+}
+
+/// `10^exp` as a fraction.
+pub fn pow10<T: Float>(exp: i32) -> SafeDecimal<T> {
+    let ten = SafeDecimal {
+        numerator: T::from(10).unwrap(),
+        denominator: T::one(),
+    };
+    let mut result = SafeDecimal {
+        numerator: T::one(),
+        denominator: T::one(),
+    };
+    for _ in 0..exp.abs() {
+        result *= ten;
+    }
+
+    if exp < 0 {
+        result.inv().unwrap()
+    } else {
+        result
+    }
+}
+
+/// Exponent of the most significant decimal digit, i.e. `floor(log10(|value|))`.
+///
+/// `value` must not be zero.
+pub fn decimal_exponent<T: Float>(value: SafeDecimal<T>) -> i32 {
+    let value = value.abs();
+    let mut exp = value.to_float().log10().floor().to_i32().unwrap_or(0);
+
+    // The float approximation can be off by one on either side.
+    while value >= pow10(exp + 1) {
+        exp += 1;
+    }
+    while value < pow10(exp) {
+        exp -= 1;
+    }
+    exp
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::d;
+    use crate::RoundingDirection;
+
+    #[test]
+    fn it_rounds_to_integers() {
+        assert_eq!(round_integer(d(2.5), &Rounding::HALF_EVEN), d(2.0));
+        assert_eq!(round_integer(d(3.5), &Rounding::HALF_EVEN), d(4.0));
+        assert_eq!(round_integer(d(-2.5), &Rounding::HALF_UP), d(-3.0));
+        assert_eq!(round_integer(d(-2.5), &Rounding::HALF_CEIL), d(-2.0));
+        assert_eq!(round_integer(d(2.1), &Rounding::UP), d(3.0));
+        assert_eq!(round_integer(d(-2.1), &Rounding::FLOOR), d(-3.0));
+        assert_eq!(round_integer(d(-2.9), &Rounding::DOWN), d(-2.0));
+        assert_eq!(round_integer(d(7.0), &Rounding::UP), d(7.0));
+    }
+
+    #[test]
+    fn it_rounds_to_significant_digits() {
+        let third = d(1.0) / d(3.0);
+        assert_eq!(round_significant(third, 4, &Rounding::HALF_EVEN), d(0.3333));
+        assert_eq!(
+            round_significant(third * d(2.0), 4, &Rounding::HALF_EVEN),
+            d(0.6667)
+        );
+        assert_eq!(
+            round_significant(d(123456.0), 2, &Rounding::HALF_EVEN),
+            d(120000.0)
+        );
+        assert_eq!(
+            round_significant(d(-0.000123456), 3, &Rounding::DOWN),
+            d(-0.000123)
+        );
+        assert_eq!(round_significant(d(9.99), 2, &Rounding::HALF_UP), d(10.0));
+    }
+
+    #[test]
+    fn it_rounds_to_decimal_places() {
+        assert_eq!(d(2.675).round_dp(2, Rounding::HALF_EVEN), d(2.68));
+        assert_eq!(d(2.665).round_dp(2, Rounding::HALF_EVEN), d(2.66));
+        assert_eq!(d(-2.665).round_dp(2, Rounding::HALF_UP), d(-2.67));
+        assert_eq!(d(1.23456).round_dp(3, Rounding::FLOOR), d(1.234));
+        assert_eq!(d(-1.23456).round_dp(3, Rounding::FLOOR), d(-1.235));
+        assert_eq!(d(1.5).round_dp(0, Rounding::HALF_EVEN), d(2.0));
+        assert_eq!(d(1.5).round_dp(4, Rounding::UP), d(1.5));
+        assert_eq!(d(1.5).round_dp(400, Rounding::UP), d(1.5));
+        // 2^-80 takes exactly 80 decimals.
+        let tiny = SafeDecimal::new(2f64.powi(-80), 1.0).unwrap();
+        assert_eq!(tiny.round_dp(23, Rounding::HALF_EVEN), d(0.0));
+        assert_eq!(tiny.round_dp(80, Rounding::UP), tiny);
+        assert!((-tiny).round_dp(23, Rounding::UP) < -tiny * d(10.0));
+        let huge = SafeDecimal::new(1e300, 1.0).unwrap();
+        assert_eq!(huge.round_dp(2, Rounding::UP), huge);
+
+        let third = d(1.0) / d(3.0);
+        assert_eq!(third.round_dp(4, Rounding::HALF_EVEN), d(0.3333));
+        assert_eq!(
+            third
+                .round_dp(4, Rounding::HALF_EVEN)
+                .to_decimal(Default::default()),
+            "0.3333"
+        );
+        assert_eq!((third * d(2.0)).round_dp(2, Rounding::DOWN), d(0.66));
+    }
+
+    #[test]
+    fn it_rounds_like_floats() {
+        for value in [2.5, -2.5, 2.4, -2.6, 3.0, -3.0, 0.5, -0.5, 1e20, 0.0] {
+            assert_eq!(d(value).floor().to_float(), value.floor());
+            assert_eq!(d(value).ceil().to_float(), value.ceil());
+            assert_eq!(d(value).trunc().to_float(), value.trunc());
+            assert_eq!(d(value).round().to_float(), value.round());
+        }
+        assert_eq!(d(2.5).round_to(Rounding::HALF_EVEN), d(2.0));
+        assert_eq!(d(-2.5).round_to(Rounding::HALF_CEIL), d(-2.0));
+    }
+
+    #[test]
+    fn it_rounds_to_increments() {
+        let nickel = d(0.05);
+        assert_eq!(
+            d(1.23).round_to_increment(nickel, Rounding::HALF_UP),
+            d(1.25)
+        );
+        assert_eq!(
+            d(1.22).round_to_increment(nickel, Rounding::HALF_UP),
+            d(1.2)
+        );
+        assert_eq!(
+            d(-1.23).round_to_increment(nickel, Rounding::FLOOR),
+            d(-1.25)
+        );
+        assert_eq!(d(1.25).round_to_increment(nickel, Rounding::UP), d(1.25));
+        assert_eq!(d(1.23).round_to_increment(-nickel, Rounding::DOWN), d(1.2));
+
+        let eighth = d(1.0) / d(8.0);
+        assert_eq!(
+            d(0.3).round_to_increment(eighth, Rounding::HALF_EVEN),
+            d(0.25)
+        );
+        let third = d(1.0) / d(3.0);
+        assert_eq!(
+            d(0.5).round_to_increment(third, Rounding::CEIL),
+            third * d(2.0)
+        );
+        assert_eq!(d(0.1).round_to_increment(third, Rounding::HALF_UP), d(0.0));
+        assert_eq!(d(0.2).round_to_increment(third, Rounding::HALF_UP), third);
+        // Just above 4/3, by less than the float products in `/` can tell.
+        let above = SafeDecimal::new(9007199254740991.0, 6755399441055743.0).unwrap();
+        assert_eq!(
+            above.round_to_increment(third, Rounding::CEIL),
+            third * d(5.0)
+        );
+        assert_eq!(
+            above.round_to_increment(third, Rounding::FLOOR),
+            third * d(4.0)
+        );
+        // Quarter hours
+        assert_eq!(d(7.6).round_to_increment(d(0.25), Rounding::CEIL), d(7.75));
+        assert_eq!(
+            d(12.0).round_to_increment(d(5.0), Rounding::HALF_EVEN),
+            d(10.0)
+        );
+    }
+
+    #[test]
+    fn it_rounds_ties_to_increments_in_every_mode() {
+        let nickel = d(0.05);
+        let cases = [
+            (Rounding::UP, 1.25, -1.25),
+            (Rounding::DOWN, 1.2, -1.2),
+            (Rounding::CEIL, 1.25, -1.2),
+            (Rounding::FLOOR, 1.2, -1.25),
+            (Rounding::EVEN, 1.2, -1.2),
+            (Rounding::HALF_UP, 1.25, -1.25),
+            (Rounding::HALF_DOWN, 1.2, -1.2),
+            (Rounding::HALF_CEIL, 1.25, -1.2),
+            (Rounding::HALF_FLOOR, 1.2, -1.25),
+            (Rounding::HALF_EVEN, 1.2, -1.2),
+        ];
+        for (rounding, positive, negative) in cases {
+            assert_eq!(
+                d(1.225).round_to_increment(nickel, rounding.clone()),
+                d(positive)
+            );
+            assert_eq!(d(-1.225).round_to_increment(nickel, rounding), d(negative));
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn it_panics_with_a_zero_increment() {
+        d(1.0).round_to_increment(d(0.0), Rounding::HALF_UP);
+    }
+
+    #[test]
+    fn it_rounds_to_odd_and_05up() {
+        assert_eq!(d(2.5).round_to(Rounding::HALF_ODD), d(3.0));
+        assert_eq!(d(3.5).round_to(Rounding::HALF_ODD), d(3.0));
+        assert_eq!(d(-2.5).round_to(Rounding::HALF_ODD), d(-3.0));
+        assert_eq!(d(2.6).round_to(Rounding::HALF_ODD), d(3.0));
+        assert_eq!(d(2.1).round_to(Rounding::ODD), d(3.0));
+        assert_eq!(d(3.9).round_to(Rounding::ODD), d(3.0));
+
+        assert_eq!(d(1.01).round_dp(1, Rounding::ZERO_FIVE_UP), d(1.1));
+        assert_eq!(d(1.09).round_dp(1, Rounding::ZERO_FIVE_UP), d(1.1));
+        assert_eq!(d(1.59).round_dp(1, Rounding::ZERO_FIVE_UP), d(1.6));
+        assert_eq!(d(1.69).round_dp(1, Rounding::ZERO_FIVE_UP), d(1.6));
+        assert_eq!(d(1.51).round_dp(1, Rounding::ZERO_FIVE_UP), d(1.6));
+        assert_eq!(d(-1.01).round_dp(1, Rounding::ZERO_FIVE_UP), d(-1.1));
+        assert_eq!(d(12.5).round_to(Rounding::ZERO_FIVE_UP), d(12.0));
+        assert_eq!(d(10.5).round_to(Rounding::ZERO_FIVE_UP), d(11.0));
+
+        let half_05up = Rounding::new(RoundingDirection::ZeroFiveUp, true);
+        assert_eq!(d(1.04).round_dp(1, half_05up.clone()), d(1.0));
+        assert_eq!(d(1.05).round_dp(1, half_05up.clone()), d(1.1));
+        assert_eq!(d(1.15).round_dp(1, half_05up), d(1.1));
+        assert_eq!(
+            d(2.5).round_to(Rounding::new(RoundingDirection::Even, true)),
+            d(2.0)
+        );
+    }
+
+    #[test]
+    fn it_rounds_stochastically() {
+        let rounded = |seed| {
+            let rounding = Rounding::stochastic(seed);
+            (0..1000)
+                .map(|_| d(0.3).round_to(rounding.clone()))
+                .collect::<Vec<_>>()
+        };
+        let values = rounded(42);
+        assert!(values.iter().all(|v| *v == d(0.0) || *v == d(1.0)));
+        let ups = values.iter().filter(|v| **v == d(1.0)).count();
+        assert!((250..350).contains(&ups), "{ups} values rounded up");
+        assert_eq!(values, rounded(42));
+        assert_ne!(values, rounded(7));
+
+        let rounding = Rounding::stochastic(1);
+        let ups = (0..1000)
+            .filter(|_| d(-0.75).round_dp(0, rounding.clone()) == d(-1.0))
+            .count();
+        assert!((700..800).contains(&ups), "{ups} values rounded up");
+
+        // Exact values are never rounded
+        let rounding = Rounding::stochastic(3);
+        assert!((0..100).all(|_| d(2.0).round_to(rounding.clone()) == d(2.0)));
+    }
+
+    #[test]
+    fn it_takes_the_fractional_part() {
+        assert_eq!(d(3.75).fract(), d(0.75));
+        assert_eq!(d(-3.75).fract(), d(-0.75));
+        assert_eq!(d(3.0).fract(), d(0.0));
+        let third = d(10.0) / d(3.0);
+        assert_eq!(third.fract(), d(1.0) / d(3.0));
+        assert_eq!(third.trunc() + third.fract(), third);
+    }
+
+    #[test]
+    fn it_rounds_large_fractions_exactly() {
+        let sevenths = SafeDecimal::new(1e30, 7.0).unwrap();
+        let integer = sevenths.trunc();
+        assert_eq!(integer, d(142857142857142857142857142857.0));
+        // The float closest to 1e30 is 1000000000000000019884624838656, which is 5 modulo 7.
+        assert_eq!(sevenths.fract(), d(5.0) / d(7.0));
+        assert_eq!(sevenths.floor(), integer);
+        assert_eq!(sevenths.round(), integer + d(1.0));
+        assert_eq!((-sevenths).ceil(), -integer);
+        assert_eq!((-sevenths).fract(), -sevenths.fract());
+
+        let thirds = d(1e17) / d(3.0);
+        assert_eq!(thirds.fract(), d(1.0) / d(3.0));
+        assert_eq!(thirds.round_to(Rounding::UP), d(33333333333333334.0));
+        assert_eq!(thirds.round(), d(33333333333333333.0));
+    }
+
+    #[test]
+    fn it_rounds_values_beyond_the_float_range() {
+        let max = SafeDecimal::new(f64::MAX, 1.0).unwrap();
+        let squared = max * max;
+        assert!(squared.numerator.is_finite() && squared.denominator.is_finite());
+        assert_eq!(squared.round_dp(2, Rounding::HALF_EVEN), squared);
+        assert_eq!(squared.floor(), squared);
+        assert_eq!(squared.ceil(), squared);
+        assert_eq!(squared.trunc(), squared);
+        assert_eq!((-squared).round(), -squared);
+        assert_eq!(squared.round_to(Rounding::UP), squared);
+
+        let huge = SafeDecimal::new(2f64.powi(550), 2f64.powi(-550)).unwrap(); // 2^1100
+        assert_eq!(huge.floor(), huge);
+        assert_eq!((huge * d(3.0)).ceil(), huge * d(3.0));
+        assert_eq!((-huge).round_dp(2, Rounding::UP), -huge);
+
+        let big = SafeDecimal::new(1e200, 1.0).unwrap();
+        let product = big * big;
+        let floor = product.floor();
+        assert!(floor.numerator.is_finite() && floor.to_float().is_infinite());
+        assert!(((floor / product).to_float() - 1.0).abs() < 1e-15);
+    }
+
+    #[test]
+    fn it_rounds_to_increments_beyond_the_float_range() {
+        let max = SafeDecimal::new(f64::MAX, 1.0).unwrap();
+        let squared = max * max;
+        let quarter = d(0.25);
+        assert_eq!(
+            squared.round_to_increment(quarter, Rounding::HALF_EVEN),
+            squared
+        );
+        assert_eq!(
+            (-squared).round_to_increment(d(3.0), Rounding::FLOOR),
+            -squared
+        );
+        let third = d(1.0) / d(3.0);
+        assert_eq!(squared.round_to_increment(third, Rounding::UP), squared);
+        assert_eq!(squared.fract(), d(0.0));
+
+        let huge = SafeDecimal::new(2f64.powi(550), 2f64.powi(-550)).unwrap(); // 2^1100
+        assert_eq!(
+            (huge * d(3.0)).round_to_increment(huge, Rounding::DOWN),
+            huge * d(3.0)
+        );
+    }
+
+    #[test]
+    fn it_finds_the_decimal_exponent() {
+        assert_eq!(decimal_exponent(d(1.0)), 0);
+        assert_eq!(decimal_exponent(d(9.999)), 0);
+        assert_eq!(decimal_exponent(d(10.0)), 1);
+        assert_eq!(decimal_exponent(d(-0.001)), -3);
+        assert_eq!(decimal_exponent(d(0.00099)), -4);
+        assert_eq!(decimal_exponent(d(1e22)), 22);
+    }
+}
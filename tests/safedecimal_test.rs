@@ -1,3 +1,6 @@
+// These compare plain `value = value + x` loops against other crates, so keep them written out.
+#![allow(clippy::assign_op_pattern, clippy::to_string_in_format_args)]
+
 use bigdecimal::BigDecimal;
 use num_rational::Rational64;
 use num_traits::{FromPrimitive, ToPrimitive};